@@ -1,4 +1,7 @@
-use aoc2021::{day1, day2, day3, day4, day5};
+use aoc2021::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day2, day3, day4, day5,
+    day6, day7, day8, day9,
+};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 fn get_data_dir() -> std::path::PathBuf {
@@ -86,7 +89,228 @@ fn day5_benchmark(c: &mut Criterion) {
     });
 
     c.bench_function("day 5 star 2", |b| {
-        b.iter(|| day5::star2(black_box(&mut day5_data.clone())))
+        b.iter(|| day5::star2(black_box(&day5_data)))
+    });
+}
+
+fn day6_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 6 setup", |b| {
+        b.iter(|| day6::data(black_box(&data_dir)))
+    });
+    let day6_data = day6::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 6 star 1", |b| {
+        b.iter(|| day6::star1(black_box(&day6_data)))
+    });
+
+    c.bench_function("day 6 star 2", |b| {
+        b.iter(|| day6::star2(black_box(&day6_data)))
+    });
+}
+
+fn day7_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 7 setup", |b| {
+        b.iter(|| day7::data(black_box(&data_dir)))
+    });
+    let day7_data = day7::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 7 star 1", |b| {
+        b.iter(|| day7::star1(black_box(&day7_data)))
+    });
+
+    c.bench_function("day 7 star 2", |b| {
+        b.iter(|| day7::star2(black_box(&day7_data)))
+    });
+}
+
+fn day8_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 8 setup", |b| {
+        b.iter(|| day8::data(black_box(&data_dir)))
+    });
+    let day8_data = day8::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 8 star 1", |b| {
+        b.iter(|| day8::star1(black_box(&day8_data)))
+    });
+
+    c.bench_function("day 8 star 2", |b| {
+        b.iter(|| day8::star2(black_box(&day8_data)))
+    });
+}
+
+fn day9_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 9 setup", |b| {
+        b.iter(|| day9::data(black_box(&data_dir)))
+    });
+    let day9_data = day9::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 9 star 1", |b| {
+        b.iter(|| day9::star1(black_box(&day9_data)))
+    });
+
+    c.bench_function("day 9 star 2", |b| {
+        b.iter(|| day9::star2(black_box(&day9_data)))
+    });
+}
+
+fn day10_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 10 setup", |b| {
+        b.iter(|| day10::data(black_box(&data_dir)))
+    });
+    let day10_data = day10::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 10 star 1", |b| {
+        b.iter(|| day10::star1(black_box(&day10_data)))
+    });
+
+    c.bench_function("day 10 star 2", |b| {
+        b.iter(|| day10::star2(black_box(&day10_data)))
+    });
+}
+
+fn day11_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 11 setup", |b| {
+        b.iter(|| day11::data(black_box(&data_dir)))
+    });
+    let day11_data = day11::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 11 star 1", |b| {
+        b.iter(|| day11::star1(black_box(day11_data.clone())))
+    });
+
+    c.bench_function("day 11 star 2", |b| {
+        b.iter(|| day11::star2(black_box(day11_data.clone())))
+    });
+}
+
+fn day12_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 12 setup", |b| {
+        b.iter(|| day12::data(black_box(&data_dir)))
+    });
+    let day12_data = day12::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 12 star 1", |b| {
+        b.iter(|| day12::star1(black_box(&day12_data)))
+    });
+
+    c.bench_function("day 12 star 2", |b| {
+        b.iter(|| day12::star2(black_box(&day12_data)))
+    });
+}
+
+fn day13_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 13 setup", |b| {
+        b.iter(|| day13::data(black_box(&data_dir)))
+    });
+    let (points, folds) = day13::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 13 star 1", |b| {
+        b.iter(|| day13::star1(black_box((points.clone(), &folds))))
+    });
+
+    c.bench_function("day 13 star 2", |b| {
+        b.iter(|| day13::star2(black_box((points.clone(), &folds))))
+    });
+}
+
+fn day14_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 14 setup", |b| {
+        b.iter(|| day14::data(black_box(&data_dir)))
+    });
+    let (polymer, insertions) = day14::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 14 star 1", |b| {
+        b.iter(|| day14::star1(black_box(&polymer), black_box(&insertions)))
+    });
+
+    c.bench_function("day 14 star 2", |b| {
+        b.iter(|| day14::star2(black_box(&polymer), black_box(&insertions)))
+    });
+}
+
+fn day15_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 15 setup", |b| {
+        b.iter(|| day15::data(black_box(&data_dir)))
+    });
+    let day15_data = day15::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 15 star 1", |b| {
+        b.iter(|| day15::star1(black_box(&day15_data)))
+    });
+
+    c.bench_function("day 15 star 2", |b| {
+        b.iter(|| day15::star2(black_box(&day15_data)))
+    });
+}
+
+fn day16_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 16 setup", |b| {
+        b.iter(|| day16::data(black_box(&data_dir)))
+    });
+    let day16_data = day16::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 16 star 1", |b| {
+        b.iter(|| day16::star1(black_box(&day16_data)))
+    });
+
+    c.bench_function("day 16 star 2", |b| {
+        b.iter(|| day16::star2(black_box(&day16_data)))
+    });
+}
+
+fn day17_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 17 setup", |b| {
+        b.iter(|| day17::data(black_box(&data_dir)))
+    });
+    let day17_data = day17::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 17 star 1", |b| {
+        b.iter(|| day17::star1(black_box(&day17_data)))
+    });
+
+    c.bench_function("day 17 star 2", |b| {
+        b.iter(|| day17::star2(black_box(&day17_data)))
+    });
+}
+
+fn day18_benchmark(c: &mut Criterion) {
+    let data_dir = get_data_dir();
+
+    c.bench_function("day 18 setup", |b| {
+        b.iter(|| day18::data(black_box(&data_dir)))
+    });
+    let day18_data = day18::data(&data_dir).expect("Programming error");
+
+    c.bench_function("day 18 star 1", |b| {
+        b.iter(|| day18::star1(black_box(&day18_data)))
+    });
+
+    c.bench_function("day 18 star 2", |b| {
+        b.iter(|| day18::star2(black_box(&day18_data)))
     });
 }
 
@@ -97,5 +321,18 @@ criterion_group!(
     day3_benchmark,
     day4_benchmark,
     day5_benchmark,
+    day6_benchmark,
+    day7_benchmark,
+    day8_benchmark,
+    day9_benchmark,
+    day10_benchmark,
+    day11_benchmark,
+    day12_benchmark,
+    day13_benchmark,
+    day14_benchmark,
+    day15_benchmark,
+    day16_benchmark,
+    day17_benchmark,
+    day18_benchmark,
 );
 criterion_main!(benches);
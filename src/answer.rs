@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::grid::Grid;
+
+/// A 2-D grid of lit and unlit pixels
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    pixels: Grid<bool>,
+}
+
+impl Bitmap {
+    /// An unlit bitmap of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        Bitmap {
+            pixels: Grid::new(width, height, false),
+        }
+    }
+
+    /// The smallest bitmap containing every lit `(x, y)` point, with its
+    /// origin at `(0, 0)`
+    pub fn from_points<I: IntoIterator<Item = (usize, usize)>>(points: I) -> Self {
+        let points: Vec<(usize, usize)> = points.into_iter().collect();
+        let width = points.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+        let height = points.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+        let mut bitmap = Bitmap::new(width, height);
+        for (x, y) in points {
+            bitmap.set(x, y, true);
+        }
+        bitmap
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.height()
+    }
+
+    /// Whether the pixel at `(x, y)` is lit. Anything out of bounds is unlit.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels.get(x, y).copied().unwrap_or(false)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        self.pixels[(x, y)] = lit;
+    }
+
+    /// The part of this bitmap inside the given rectangle. Any of the
+    /// rectangle outside of this bitmap is unlit.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        Bitmap {
+            pixels: Grid::from_fn(width, height, |dx, dy| self.get(x + dx, y + dy)),
+        }
+    }
+
+    /// Number of lit pixels
+    pub fn count(&self) -> usize {
+        self.pixels.iter().filter(|(_, &lit)| lit).count()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.pixels.rows()
+    }
+}
+
+impl From<Grid<bool>> for Bitmap {
+    fn from(pixels: Grid<bool>) -> Self {
+        Bitmap { pixels }
+    }
+}
+
+impl fmt::Display for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for lit in row {
+                write!(f, "{}", if *lit { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The answer to a single star of a puzzle
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    Bitmap(Bitmap),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Bitmap(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_owned())
+    }
+}
+
+impl From<Bitmap> for Answer {
+    fn from(b: Bitmap) -> Self {
+        Answer::Bitmap(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap() {
+        let bitmap = Bitmap::from_points(vec![(0, 0), (2, 0), (1, 1)]);
+        assert_eq!((bitmap.width(), bitmap.height()), (3, 2));
+        assert!(bitmap.get(2, 0));
+        assert!(!bitmap.get(1, 0));
+        assert!(!bitmap.get(5, 5));
+        assert_eq!(bitmap.count(), 3);
+        assert_eq!(bitmap.to_string(), "#.#\n.#.");
+        assert_eq!(Bitmap::from_points(vec![]).to_string(), "");
+        assert_eq!(bitmap.crop(1, 0, 3, 2).to_string(), ".#.\n#..");
+        let grid = Grid::from_rows(vec![vec![true, false], vec![false, true]]).unwrap();
+        assert_eq!(
+            Bitmap::from(grid),
+            Bitmap::from_points(vec![(0, 0), (1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(7u32), Answer::Integer(7));
+        assert_eq!(Answer::from(-3isize).to_string(), "-3");
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Answer::from("EPZGKCHU"), Answer::Text("EPZGKCHU".into()));
+    }
+}
@@ -0,0 +1,267 @@
+//! Unsigned integers of any size, for answers that outgrow `u64`.
+//!
+//! Only the handful of operations the puzzles need are here: addition,
+//! multiplication, comparison, and conversion to and from decimal.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+use std::str::FromStr;
+
+/// An unsigned integer, stored as base 2^32 digits from least significant
+/// to most, without leading zeros
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    digits: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// The value, if it fits in a `u64`
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.digits.len() > 2 {
+            None
+        } else {
+            Some(self.low_u64())
+        }
+    }
+
+    /// The value modulo 2^64
+    pub fn low_u64(&self) -> u64 {
+        self.digits
+            .iter()
+            .take(2)
+            .rev()
+            .fold(0, |value, digit| (value << 32) | *digit as u64)
+    }
+
+    /// The number of bits needed to write the value down, which is zero
+    /// for zero
+    pub fn bits(&self) -> usize {
+        match self.digits.last() {
+            Some(top) => self.digits.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Whether the bit worth 2^`index` is set
+    pub fn bit(&self, index: usize) -> bool {
+        self.digits
+            .get(index / 32)
+            .is_some_and(|digit| (digit >> (index % 32)) & 1 == 1)
+    }
+
+    fn trim(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        self
+    }
+
+    /// Multiply by a small factor and add a small amount in place
+    fn mul_add_small(&mut self, factor: u32, addend: u32) {
+        let mut carry = addend as u64;
+        for digit in self.digits.iter_mut() {
+            let product = *digit as u64 * factor as u64 + carry;
+            *digit = product as u32;
+            carry = product >> 32;
+        }
+        if carry > 0 {
+            self.digits.push(carry as u32);
+        }
+        *self = std::mem::take(self).trim();
+    }
+
+    /// Divide by a small divisor in place, returning the remainder
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for digit in self.digits.iter_mut().rev() {
+            let value = (remainder << 32) | *digit as u64;
+            *digit = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        *self = std::mem::take(self).trim();
+        remainder as u32
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint {
+            digits: vec![value as u32, (value >> 32) as u32],
+        }
+        .trim()
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.digits.len() >= other.digits.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut digits = Vec::with_capacity(long.digits.len() + 1);
+        let mut carry = 0u64;
+        for (i, digit) in long.digits.iter().enumerate() {
+            let sum = *digit as u64 + *short.digits.get(i).unwrap_or(&0) as u64 + carry;
+            digits.push(sum as u32);
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+        BigUint { digits }
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.digits.iter().enumerate() {
+                let product = *a as u64 * *b as u64 + digits[i + j] as u64 + carry;
+                digits[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigUint { digits }.trim()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The error from parsing anything but decimal digits as a `BigUint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid unsigned decimal integer")
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseBigUintError);
+        }
+        let mut value = BigUint::zero();
+        for c in s.chars() {
+            let digit = c.to_digit(10).ok_or(ParseBigUintError)?;
+            value.mul_add_small(10, digit);
+        }
+        Ok(value)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Peel off nine decimal digits at a time, least significant first
+        const CHUNK: u32 = 1_000_000_000;
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        loop {
+            chunks.push(rest.div_rem_small(CHUNK));
+            if rest.is_zero() {
+                break;
+            }
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap_or(&0))?;
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigUint {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(0), BigUint::zero());
+        assert_eq!(BigUint::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(big("000123").to_string(), "123");
+        assert_eq!(big("1000000000").to_string(), "1000000000");
+        assert_eq!(big("18446744073709551615").to_u64(), Some(u64::MAX));
+        assert_eq!(big("18446744073709551616").to_u64(), None);
+        assert_eq!(big("18446744073709551617").low_u64(), 1);
+        assert!("".parse::<BigUint>().is_err());
+        assert!("-1".parse::<BigUint>().is_err());
+        assert!("12a".parse::<BigUint>().is_err());
+
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert_eq!(BigUint::from(5).bits(), 3);
+        let two_64 = big("18446744073709551616");
+        assert_eq!(two_64.bits(), 65);
+        assert!(two_64.bit(64));
+        assert!(!two_64.bit(63));
+        assert!(!two_64.bit(1000));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        let one = BigUint::from(1);
+        assert_eq!((&max + &one).to_string(), "18446744073709551616");
+        assert_eq!(
+            (&max * &max).to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(&max * &BigUint::zero(), BigUint::zero());
+        assert_eq!(&BigUint::zero() + &one, one);
+        let a = big("123456789012345678901234567890");
+        let b = big("987654321098765432109876543210");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!((&a + &b).to_string(), "1111111110111111111011111111100");
+
+        assert!(a < b);
+        assert!(max < &max + &one);
+        assert!(one < max);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    }
+}
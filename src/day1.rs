@@ -1,11 +1,17 @@
 use anyhow::Result;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "1.txt";
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<u32>> {
     data.lines()
         .map(|s_res| {
             s_res
@@ -15,6 +21,10 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
         .collect()
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<u32>> {
+    parse_reader(data.as_bytes())
+}
+
 pub fn star1(data: &[u32]) -> u32 {
     data.windows(2)
         .filter(|window| window[1] > window[0])
@@ -28,19 +38,49 @@ pub fn star2(data: &[u32]) -> u32 {
         .count() as u32
 }
 
+pub struct Day1;
+
+impl Puzzle for Day1 {
+    const DAY: u8 = 1;
+    type Input = Vec<u32>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+    const SAMPLE_DATA: &str = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_str(SAMPLE_DATA).unwrap(),
+            vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263]
+        );
+        assert!(parse_str("199\nabc\n").is_err());
+    }
 
     #[test]
     fn test_star1() {
-        assert_eq!(star1(&SAMPLE_DATA), 7);
+        let data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star1(&data), 7);
     }
 
     #[test]
     fn test_star2() {
-        assert_eq!(star2(&SAMPLE_DATA), 5);
+        let data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&data), 5);
     }
 }
@@ -1,16 +1,26 @@
 use anyhow::Result;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "10.txt";
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<String>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<String>> {
     data.lines()
         .map(|s_res| s_res.map_err(|e| e.into()))
         .collect()
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<String>> {
+    parse_reader(data.as_bytes())
+}
+
 fn bracket_match(b: u8) -> u8 {
     match b {
         b'(' => b')',
@@ -84,14 +94,33 @@ pub fn star2(data: &[String]) -> u64 {
         })
         .collect();
     scores.sort_unstable();
-    scores[(scores.len() / 2)]
+    scores[scores.len() / 2]
+}
+
+pub struct Day10;
+
+impl Puzzle for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<String>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [&'static str; 10] = [
+    const SAMPLE_DATA: [&str; 10] = [
         "[({(<(())[]>[[{[]{<()<>>",
         "[(()[<>])]({[<{<<[]>>(",
         "{([(<{}[<>[]}>{[]{[(<()>",
@@ -106,13 +135,13 @@ mod tests {
 
     #[test]
     fn test_star1() {
-        let sample_data: Vec<String> = SAMPLE_DATA.iter().map(|r| r.to_string()).collect();
+        let sample_data = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star1(&sample_data), 26397);
     }
 
     #[test]
     fn test_star2() {
-        let sample_data: Vec<String> = SAMPLE_DATA.iter().map(|r| r.to_string()).collect();
+        let sample_data = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star2(&sample_data), 288957);
     }
 }
@@ -2,6 +2,9 @@ use anyhow::Result;
 use std::collections::HashSet;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "12.txt";
 
 fn solve_recursive(
@@ -144,12 +147,19 @@ impl Maze {
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Maze> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Maze> {
     data.lines()
         .map(|s_res| s_res.map_err(|e| e.into()))
         .collect::<Result<Maze>>()
 }
 
+pub fn parse_str(data: &str) -> Result<Maze> {
+    parse_reader(data.as_bytes())
+}
+
 pub fn star1(data: &Maze) -> u32 {
     let mut solutions = HashSet::with_capacity(200);
     solve_all(data, &mut solutions, true);
@@ -162,23 +172,48 @@ pub fn star2(data: &Maze) -> u32 {
     solutions.len() as u32
 }
 
+pub struct Day12;
+
+impl Puzzle for Day12 {
+    const DAY: u8 = 12;
+    type Input = Maze;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE1: [&'static str; 7] =
-        ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"];
+    const SAMPLE1: [&str; 7] = ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"];
 
-    const SAMPLE2: [&'static str; 10] = [
+    const SAMPLE2: [&str; 10] = [
         "dc-end", "HN-start", "start-kj", "dc-start", "dc-HN", "LN-dc", "HN-end", "kj-sa", "kj-HN",
         "kj-dc",
     ];
 
-    const SAMPLE3: [&'static str; 18] = [
+    const SAMPLE3: [&str; 18] = [
         "fs-end", "he-DX", "fs-he", "start-DX", "pj-DX", "end-zg", "zg-sl", "zg-pj", "pj-he",
         "RW-he", "fs-DX", "pj-RW", "zg-RW", "start-pj", "he-WI", "zg-he", "pj-fs", "start-RW",
     ];
 
+    #[test]
+    fn test_parse_str() {
+        let maze = parse_str(&SAMPLE1.join("\n")).unwrap();
+        assert_eq!(maze.adjacencies.len(), 6);
+        assert_eq!(maze.minors.len(), 3);
+    }
+
     #[test]
     fn test_star1() {
         let maze1 = SAMPLE1.iter().cloned().collect();
@@ -1,8 +1,17 @@
+use anyhow::Result;
 use std::collections::HashSet;
 use std::io::BufRead;
 
+use crate::answer::{Answer, Bitmap};
+use crate::ocr;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "13.txt";
 
+/// The dotted points on the transparent paper, and the folds to apply to it
+pub type Manual = (HashSet<(u32, u32)>, Vec<AxisFold>);
+
 #[derive(Debug, Clone)]
 pub enum AxisFold {
     X(u32),
@@ -24,42 +33,58 @@ impl AxisFold {
     }
 }
 
-pub fn parse<I: Iterator<Item = String>>(mut data: I) -> (HashSet<(u32, u32)>, Vec<AxisFold>) {
+fn parse_point(parser: &mut LineParser) -> Result<(u32, u32), ParseError> {
+    let x = parser.integer()?;
+    parser.expect(",")?;
+    let y = parser.integer()?;
+    parser.end()?;
+    Ok((x, y))
+}
+
+fn parse_fold(parser: &mut LineParser) -> Result<AxisFold, ParseError> {
+    parser.expect("fold along ")?;
+    let axis = parser.char_matching("'x' or 'y'", |c| c == 'x' || c == 'y')?;
+    parser.expect("=")?;
+    let n = parser.integer()?;
+    parser.end()?;
+    Ok(if axis == 'x' {
+        AxisFold::X(n)
+    } else {
+        AxisFold::Y(n)
+    })
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Manual, ParseError> {
     let mut points: HashSet<(u32, u32)> = HashSet::with_capacity(50);
     let mut folds: Vec<AxisFold> = Vec::with_capacity(10);
 
-    for line in data.by_ref() {
+    let mut lines = data.enumerate().map(|(i, line)| (i + 1, line));
+    for (line_no, line) in lines.by_ref() {
         if line.trim().is_empty() {
             break;
         }
-        points.insert(
-            line.split_once(',')
-                .map(|(x, y)| (x.parse().unwrap(), y.parse().unwrap()))
-                .unwrap(),
-        );
+        points.insert(parse_point(&mut LineParser::new(line_no, &line))?);
     }
 
-    for line in data {
-        assert!(line.starts_with("fold along "));
-        let fold_str = line.strip_prefix("fold along ").map(|s| s.trim()).unwrap();
-        let fold = fold_str
-            .split_once('=')
-            .map(|a| match a.0 {
-                "x" => AxisFold::X(a.1.parse().unwrap()),
-                "y" => AxisFold::Y(a.1.parse().unwrap()),
-                _ => panic!(),
-            })
-            .unwrap();
-        folds.push(fold);
+    for (line_no, line) in lines {
+        folds.push(parse_fold(&mut LineParser::new(line_no, &line))?);
     }
 
-    (points, folds)
+    Ok((points, folds))
 }
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> impl Iterator<Item = String> {
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Manual> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap())
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Manual> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Manual> {
+    parse_reader(data.as_bytes())
 }
 
 pub fn star1(data: (HashSet<(u32, u32)>, &[AxisFold])) -> u32 {
@@ -71,32 +96,45 @@ pub fn star1(data: (HashSet<(u32, u32)>, &[AxisFold])) -> u32 {
     final_field.len() as u32
 }
 
-pub fn star2(data: (HashSet<(u32, u32)>, &[AxisFold])) -> u32 {
+pub fn star2(data: (HashSet<(u32, u32)>, &[AxisFold])) -> Bitmap {
     let (points, folds) = data;
     let final_field = folds
         .iter()
         .fold(points, |points, fold| fold.apply(&points));
 
-    let max_x = final_field.iter().max_by_key(|(x, _)| x).unwrap().0;
-    let max_y = final_field.iter().max_by_key(|(_, y)| y).unwrap().1;
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            if final_field.contains(&(x, y)) {
-                print!("#");
-            } else {
-                print!(" ");
-            }
-        }
-        println!();
+    Bitmap::from_points(
+        final_field
+            .into_iter()
+            .map(|(x, y)| (x as usize, y as usize)),
+    )
+}
+
+pub struct Day13;
+
+impl Puzzle for Day13 {
+    const DAY: u8 = 13;
+    type Input = Manual;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1((input.0.clone(), &input.1)).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        // The folded paper spells out the answer in capital letters
+        let paper = star2((input.0.clone(), &input.1));
+        Ok(ocr::recognize(&paper)?.into())
     }
-    final_field.len() as u32
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [&'static str; 21] = [
+    const SAMPLE_DATA: [&str; 21] = [
         "6,10",
         "0,14",
         "9,10",
@@ -120,15 +158,40 @@ mod tests {
         "fold along x=5",
     ];
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_str("6,10\n0,\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let err = parse_str("6,10\n\nfold along z=7\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (3, 12));
+        assert_eq!(err.expected, "'x' or 'y'");
+
+        assert!(parse_str("6;10\n").is_err());
+        assert!(parse_str("6,10\n\nfold along y=\n").is_err());
+        assert!(parse_str("6,10\n\nfold y=7\n").is_err());
+        assert!(parse_str("6,10,3\n").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
     #[test]
     fn test_star1() {
-        let (points, folds) = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
+        let (points, folds) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star1((points.clone(), &folds)), 17);
     }
 
     #[test]
     fn test_star2() {
-        let (points, folds) = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
-        assert_eq!(star2((points.clone(), &folds)), 16);
+        let (points, folds) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        let paper = star2((points.clone(), &folds));
+        assert_eq!(paper.count(), 16);
+        assert_eq!(paper.to_string(), "#####\n#...#\n#...#\n#...#\n#####");
     }
 }
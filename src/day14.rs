@@ -1,28 +1,74 @@
+use anyhow::Result;
 use std::collections::HashMap;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "14.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> impl Iterator<Item = String> {
+/// The polymer template, and the pair insertion rules to apply to it
+pub type Recipe = (Vec<u8>, HashMap<(u8, u8), u8>);
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Recipe> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap())
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+fn element(parser: &mut LineParser) -> Result<u8, ParseError> {
+    parser
+        .char_matching("an element", |c| c.is_ascii_uppercase())
+        .map(|c| c as u8)
+}
+
+fn parse_template(parser: &mut LineParser) -> Result<Vec<u8>, ParseError> {
+    let mut polymer = vec![element(parser)?];
+    while !parser.is_empty() {
+        polymer.push(element(parser)?);
+    }
+    Ok(polymer)
+}
+
+fn parse_rule(parser: &mut LineParser) -> Result<((u8, u8), u8), ParseError> {
+    let a = element(parser)?;
+    let b = element(parser)?;
+    parser.expect(" -> ")?;
+    let ins = element(parser)?;
+    parser.end()?;
+    Ok(((a, b), ins))
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Recipe, ParseError> {
+    let mut lines = data.enumerate().map(|(i, line)| (i + 1, line));
+
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(1, "a polymer template"))?;
+    let polymer = parse_template(&mut LineParser::new(line_no, &line))?;
+
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(2, "a blank line"))?;
+    LineParser::new(line_no, &line).end()?;
+
+    let insertions = lines
+        .map(|(line_no, line)| parse_rule(&mut LineParser::new(line_no, &line)))
+        .collect::<Result<HashMap<(u8, u8), u8>, ParseError>>()?;
+
+    Ok((polymer, insertions))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Recipe> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
 }
 
-pub fn parse<I: Iterator<Item = String>>(mut data: I) -> (Vec<u8>, HashMap<(u8, u8), u8>) {
-    let polymer: Vec<u8> = data.next().unwrap().into_bytes();
-    let _ = data.next().unwrap();
-    let insertions: HashMap<(u8, u8), u8> = data
-        .filter_map(|rule| {
-            rule.split_once(" -> ")
-                .map(|(a, b)| ((a.as_bytes()[0], a.as_bytes()[1]), b.as_bytes()[0]))
-        })
-        .collect();
-
-    (polymer, insertions)
+pub fn parse_str(data: &str) -> Result<Recipe> {
+    parse_reader(data.as_bytes())
 }
 
-fn apply(polymer: &[u8], insertions: &HashMap<(u8, u8), u8>, count: u32) -> u64 {
+pub(crate) fn apply(polymer: &[u8], insertions: &HashMap<(u8, u8), u8>, count: u32) -> u64 {
     let mut poly_pairs: HashMap<(u8, u8), u64> = HashMap::with_capacity(50);
     for pair in polymer.windows(2) {
         let entry = poly_pairs.entry((pair[0], pair[1])).or_insert(0);
@@ -65,25 +111,68 @@ pub fn star2(polymer: &[u8], insertions: &HashMap<(u8, u8), u8>) -> u64 {
     apply(polymer, insertions, 40)
 }
 
+pub struct Day14;
+
+impl Puzzle for Day14 {
+    const DAY: u8 = 14;
+    type Input = Recipe;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(&input.0, &input.1).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(&input.0, &input.1).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [&'static str; 18] = [
+    const SAMPLE_DATA: [&str; 18] = [
         "NNCB", "", "CH -> B", "HH -> N", "CB -> H", "NH -> C", "HB -> C", "HC -> B", "HN -> C",
         "NN -> C", "BH -> H", "NC -> B", "NB -> B", "BN -> B", "BB -> N", "BC -> B", "CC -> N",
         "CN -> C",
     ];
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        let err = parse_str("NNCB\n\nCH -> B\nHH -> \n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (4, 6));
+        assert_eq!(err.expected, "\" -> \"");
+
+        assert!(parse_str("NNCB").is_err());
+        assert!(parse_str("NNCB\nCH -> B\n").is_err());
+        assert!(parse_str("NNcB\n\nCH -> B\n").is_err());
+        assert!(parse_str("NNCB\n\nCH => B\n").is_err());
+        assert!(parse_str("NNCB\n\nCH -> BB\n").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
     #[test]
     fn test_star1() {
-        let (polymer, insertions) = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
+        let (polymer, insertions) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star1(&polymer, &insertions), 1588);
     }
 
     #[test]
     fn test_star2() {
-        let (polymer, insertions) = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
+        let (polymer, insertions) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star2(&polymer, &insertions), 2188189693529);
     }
 }
@@ -1,21 +1,30 @@
+use anyhow::Result;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::parser::ParseError;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "15.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> impl Iterator<Item = String> {
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Grid<u8>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap())
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
 }
 
-pub fn parse<I: Iterator<Item = String>>(data: I) -> Vec<Vec<u8>> {
-    let mut maze: Vec<Vec<u8>> = Vec::with_capacity(100);
-    for line in data {
-        let row: Vec<u8> = line.bytes().map(|x| x - b'0').collect();
-        maze.push(row)
-    }
-    maze
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Grid<u8>, ParseError> {
+    Grid::parse_digits(data, "a risk level from 1 to 9", 1..=9)
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Grid<u8>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Grid<u8>> {
+    parse_reader(data.as_bytes())
 }
 
 #[derive(Debug, Clone, Default)]
@@ -59,38 +68,12 @@ impl Cell {
     }
 }
 
-fn find_neighbors(maze: &[Vec<u8>], point: &(usize, usize)) -> Vec<(usize, usize)> {
-    let (y, x) = *point;
-    let mut neighbors: Vec<(usize, usize)> = Vec::with_capacity(4);
-
-    let min_y = y.saturating_sub(1);
-    let min_x = x.saturating_sub(1);
-    let max_y = (maze.len() - 1).min(y + 1);
-    let max_x = (maze[max_y].len() - 1).min(x + 1);
-
-    if max_y > y {
-        neighbors.push((max_y, x));
-    }
-    if max_x > x {
-        neighbors.push((y, max_x));
-    }
-    if min_y < y {
-        neighbors.push((min_y, x));
-    }
-    if min_x < x {
-        neighbors.push((y, min_x));
-    }
-    neighbors
-}
-
-pub fn star1(maze: &[Vec<u8>]) -> u32 {
-    let max_y = maze.len() - 1;
-    let max_x = maze[max_y].len() - 1;
+pub fn star1(maze: &Grid<u8>) -> u32 {
+    let max_y = maze.height() - 1;
+    let max_x = maze.width() - 1;
     let start = (0, 0);
 
-    let mut unvisited: HashSet<(usize, usize)> = (0..=max_y)
-        .flat_map(|y| (0..=max_x).map(move |x| (y, x)))
-        .collect();
+    let mut unvisited: HashSet<(usize, usize)> = maze.points().collect();
     let mut heap: BinaryHeap<Cell> = BinaryHeap::with_capacity((max_y + 1) * (max_x + 1) / 4);
     heap.push(Cell::default());
 
@@ -102,13 +85,10 @@ pub fn star1(maze: &[Vec<u8>]) -> u32 {
     while let Some(cell) = heap.pop() {
         let score = solution_table[&cell.point].weight;
         unvisited.remove(&cell.point);
-        for neighbor in find_neighbors(maze, &cell.point)
-            .into_iter()
-            .filter(|pt| unvisited.contains(pt))
-        {
+        let (x, y) = cell.point;
+        for neighbor in maze.neighbors4(x, y).filter(|pt| unvisited.contains(pt)) {
             //println!("neighbor: {:?}", neighbor);
-            let (next_y, next_x) = neighbor;
-            let next_weight: u32 = maze[next_y][next_x] as u32;
+            let next_weight: u32 = maze[neighbor] as u32;
             let next_score: u32 = score + next_weight;
             let next_cell = Cell::new(neighbor, next_score);
             let solution_cell = Cell::new(cell.point, next_score);
@@ -117,7 +97,7 @@ pub fn star1(maze: &[Vec<u8>]) -> u32 {
                 < solution_table
                     .get(&neighbor)
                     .map(|n| n.weight)
-                    .unwrap_or(std::u32::MAX)
+                    .unwrap_or(u32::MAX)
             {
                 solution_table.insert(neighbor, solution_cell);
                 heap.push(next_cell);
@@ -126,7 +106,7 @@ pub fn star1(maze: &[Vec<u8>]) -> u32 {
         //println!("Cells to evaluate: {:?}", heap);
         //println!("Cells to evaluate: {}", unvisited.len());
     }
-    if let Some(cell) = solution_table.get(&(max_y, max_x)) {
+    if let Some(cell) = solution_table.get(&(max_x, max_y)) {
         //print_solution(&solution_table, cell.point);
         cell.weight
     } else {
@@ -134,45 +114,59 @@ pub fn star1(maze: &[Vec<u8>]) -> u32 {
     }
 }
 
-fn multiply_maze(maze: &[Vec<u8>]) -> Vec<Vec<u8>> {
-    let inner_row_count = maze.len();
-    let inner_col_count = maze[inner_row_count - 1].len();
-
-    let outer_max_y = (inner_row_count * 5) - 1;
-    let outer_max_x = (inner_col_count * 5) - 1;
-
-    let mut out: Vec<Vec<u8>> = Vec::with_capacity(outer_max_y);
-    for outer_y in 0..=outer_max_y {
-        out.push(Vec::with_capacity(outer_max_x));
+fn multiply_maze(maze: &Grid<u8>) -> Grid<u8> {
+    let inner_row_count = maze.height();
+    let inner_col_count = maze.width();
 
-        let tile_y = (outer_y / inner_row_count) as u8;
-        let inner_y = outer_y % inner_row_count;
-
-        for outer_x in 0..=outer_max_x {
+    Grid::from_fn(
+        inner_col_count * 5,
+        inner_row_count * 5,
+        |outer_x, outer_y| {
+            let tile_y = (outer_y / inner_row_count) as u8;
+            let inner_y = outer_y % inner_row_count;
             let tile_x = (outer_x / inner_col_count) as u8;
             let inner_x = outer_x % inner_col_count;
 
-            let start_val = maze[inner_y][inner_x];
+            let start_val = maze[(inner_x, inner_y)];
             let tile_offset = tile_x + tile_y;
             let new_val = ((start_val + tile_offset - 1) % 9) + 1;
 
             //println!("{} + {} + {} = {:02} => {}", start_val, tile_x, tile_y, start_val + tile_offset, new_val);
-            out[outer_y].push(new_val);
-        }
-    }
-    out
+            new_val
+        },
+    )
 }
 
-pub fn star2(maze: &[Vec<u8>]) -> u32 {
+pub fn star2(maze: &Grid<u8>) -> u32 {
     let bigger_maze = multiply_maze(maze);
     star1(&bigger_maze)
 }
 
+pub struct Day15;
+
+impl Puzzle for Day15 {
+    const DAY: u8 = 15;
+    type Input = Grid<u8>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::Found;
 
-    const SAMPLE_DATA: [&'static str; 10] = [
+    const SAMPLE_DATA: [&str; 10] = [
         "1163751742",
         "1381373672",
         "2136511328",
@@ -185,15 +179,45 @@ mod tests {
         "2311944581",
     ];
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.found.clone()), (1, Found::EndOfInput));
+
+        let err = parse_str("1163\n138\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(
+            (err.line, err.column, err.found.clone()),
+            (2, 4, Found::EndOfLine)
+        );
+
+        let err = parse_str("1163\n13813\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(
+            (err.line, err.column, err.found.clone()),
+            (2, 5, Found::Char('3'))
+        );
+
+        assert!(parse_str("1163\n13x3\n").is_err());
+        assert!(parse_str("1063\n").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
     #[test]
     fn test_star1() {
-        let maze = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
+        let maze = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star1(&maze), 40);
     }
 
     #[test]
     fn test_star2() {
-        let maze = parse(SAMPLE_DATA.iter().map(|r| r.to_string()));
+        let maze = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         let bigger_maze = multiply_maze(&maze);
         assert_eq!(star1(&bigger_maze), 315);
     }
@@ -1,75 +1,377 @@
+use anyhow::Result;
+use std::fmt;
 use std::io::BufRead;
 
 use bitvec::prelude::*;
 
+use crate::answer::Answer;
+use crate::bignum::BigUint;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+use crate::trace::Level;
+use crate::trace_event;
+
 const DATA_FILE: &str = "16.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> impl Iterator<Item = String> {
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u8>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap())
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
 }
 
-pub fn parse<I: Iterator<Item = String>>(mut data: I) -> Vec<u8> {
-    let data = data.next().unwrap();
-    data.as_bytes()
-        .chunks(2)
-        .map(|b| std::str::from_utf8(b).unwrap())
-        .map(|s| u8::from_str_radix(s, 16).unwrap())
-        .collect()
+fn hex_digit(parser: &mut LineParser) -> Result<u8, ParseError> {
+    parser
+        .char_matching("a hexadecimal digit", |c| c.is_ascii_hexdigit())
+        .map(|c| c.to_digit(16).unwrap_or_default() as u8)
 }
 
-#[derive(Debug, Clone)]
-struct Packet {
-    version: u8,
-    symbol: Symbol,
+pub fn parse<I: Iterator<Item = String>>(mut data: I) -> Result<Vec<u8>, ParseError> {
+    let line = data
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(1, "a hexadecimal transmission"))?;
+    let mut parser = LineParser::new(1, &line);
+    let mut message: Vec<u8> = Vec::with_capacity(line.len() / 2);
+    loop {
+        message.push((hex_digit(&mut parser)? << 4) | hex_digit(&mut parser)?);
+        if parser.is_empty() {
+            break;
+        }
+    }
+    Ok(message)
 }
 
-impl Packet {
-    fn parse_group(bits_in: &BitSlice<Msb0, u8>) -> (Vec<Packet>, usize) {
-        let mut out: Vec<Packet> = Vec::with_capacity(2);
-        let mut offset = 0;
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<u8>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Vec<u8>> {
+    parse_reader(data.as_bytes())
+}
+
+/// Why a transmission couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The transmission ended partway through a packet
+    Truncated {
+        /// Offset of the field that runs past the end, in bits
+        offset: usize,
+        expected: &'static str,
+    },
+    /// An operator's sub-packets didn't take up the number of bits it gave
+    /// for them
+    PayloadMismatch {
+        /// Offset of the first sub-packet
+        offset: usize,
+        declared: usize,
+        consumed: usize,
+    },
+    /// Something other than zeros followed the outermost packet
+    NonZeroPadding {
+        /// Offset of the first bit that's set
+        offset: usize,
+    },
+    /// A literal with more significant bits than fit in 64
+    LiteralTooLarge { offset: usize },
+    /// An operator without sub-packets, or a comparison without exactly two
+    InvalidOperands {
+        offset: usize,
+        type_id: u8,
+        count: usize,
+    },
+    /// An operator nested inside more than [`MAX_NESTING`] others
+    TooDeep { offset: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { offset, expected } => {
+                write!(
+                    f,
+                    "Transmission ended at bit {}, expected {}",
+                    offset, expected
+                )
+            }
+            DecodeError::PayloadMismatch {
+                offset,
+                declared,
+                consumed,
+            } => write!(
+                f,
+                "Sub-packets at bit {} took up {} bits instead of {}",
+                offset, consumed, declared
+            ),
+            DecodeError::NonZeroPadding { offset } => {
+                write!(f, "Padding at bit {} isn't zero", offset)
+            }
+            DecodeError::LiteralTooLarge { offset } => {
+                write!(f, "Literal packet at bit {} doesn't fit in 64 bits", offset)
+            }
+            DecodeError::InvalidOperands {
+                offset,
+                type_id,
+                count,
+            } => write!(
+                f,
+                "Operator packet at bit {} of type {} has {} sub-packets",
+                offset, type_id, count
+            ),
+            DecodeError::TooDeep { offset } => write!(
+                f,
+                "Operator packet at bit {} is nested more than {} deep",
+                offset, MAX_NESTING
+            ),
+        }
+    }
+}
 
-        while offset < bits_in.len() && bits_in[offset..].any() {
-            let (packet, size) = Packet::parse(&bits_in[offset..]);
-            out.push(packet);
-            //println!("Parsed packet at offset {}, moving to next packet at {}", offset, offset + size);
-            offset += size;
+impl std::error::Error for DecodeError {}
+
+/// How to deal with numbers that don't fit in a `u64`, when decoding
+/// literals or evaluating packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Keep the lowest 64 bits
+    Wrapping,
+    /// Fail
+    Checked,
+    /// Switch to integers of any size
+    Big,
+}
+
+/// Why a packet couldn't be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The value of the packet at `offset` doesn't fit in a `u64`
+    Overflow { offset: usize },
+    /// An operator without sub-packets, or a comparison without exactly two
+    InvalidOperands {
+        offset: usize,
+        type_id: u8,
+        count: usize,
+    },
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Overflow { offset } => {
+                write!(f, "Packet at bit {} overflowed 64 bits", offset)
+            }
+            EvalError::InvalidOperands {
+                offset,
+                type_id,
+                count,
+            } => write!(
+                f,
+                "Operator packet at bit {} of type {} has {} sub-packets",
+                offset, type_id, count
+            ),
         }
-        (out, offset)
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// A BITS packet, along with where it sits in the transmission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub version: u8,
+    pub type_id: u8,
+    /// For operators, whether the sub-packets are given as their length in
+    /// bits (0) or as a count of packets (1)
+    pub length_type_id: Option<u8>,
+    /// Offset of the packet's first bit from the start of the transmission
+    pub offset: usize,
+    /// Number of bits taken up by the packet, including its sub-packets
+    pub length: usize,
+    pub symbol: Symbol,
+}
+
+impl Packet {
+    /// A packet holding `symbol`, to be placed in a transmission by
+    /// [`encode`]. Until then it has no offset or length.
+    pub fn new(version: u8, symbol: Symbol) -> Self {
+        Packet {
+            version,
+            type_id: symbol.type_id(),
+            length_type_id: match symbol {
+                Symbol::Literal(_) | Symbol::WideLiteral(_) => None,
+                _ => Some(0),
+            },
+            offset: 0,
+            length: 0,
+            symbol,
+        }
+    }
+
+    pub fn children(&self) -> &[Packet] {
+        self.symbol.children()
     }
 
-    fn parse_group_n(bits_in: &BitSlice<Msb0, u8>, max_packets: usize) -> (Vec<Packet>, usize) {
-        let mut out: Vec<Packet> = Vec::with_capacity(max_packets);
-        let mut offset = 0;
+    /// Sum of the versions of this packet and every packet inside it
+    pub fn version_sum(&self) -> u32 {
+        self.version as u32 + self.children().iter().map(Packet::version_sum).sum::<u32>()
+    }
 
-        while offset < bits_in.len() && out.len() < max_packets {
-            let (packet, size) = Packet::parse(&bits_in[offset..]);
-            out.push(packet);
-            offset += size;
+    /// Evaluate the packet with `u64` arithmetic that wraps around on
+    /// overflow
+    pub fn eval(&self) -> Result<u64, EvalError> {
+        self.check_operands()?;
+        let values = self
+            .children()
+            .iter()
+            .map(Packet::eval)
+            .collect::<Result<Vec<u64>, EvalError>>()?;
+        let value = match &self.symbol {
+            Symbol::Literal(value) => *value,
+            Symbol::WideLiteral(value) => value.low_u64(),
+            Symbol::OperatorSum(_) => values
+                .iter()
+                .fold(0u64, |sum, value| sum.wrapping_add(*value)),
+            Symbol::OperatorProduct(_) => values
+                .iter()
+                .fold(1u64, |product, value| product.wrapping_mul(*value)),
+            Symbol::OperatorMin(_) => values.into_iter().min().unwrap_or_default(),
+            Symbol::OperatorMax(_) => values.into_iter().max().unwrap_or_default(),
+            Symbol::OperatorGt(_) => (values[0] > values[1]) as u64,
+            Symbol::OperatorLt(_) => (values[0] < values[1]) as u64,
+            Symbol::OperatorEq(_) => (values[0] == values[1]) as u64,
+        };
+        trace_event!(Day16::DAY, Level::Trace, "packet evaluated", value = value);
+        Ok(value)
+    }
+
+    fn check_operands(&self) -> Result<(), EvalError> {
+        if self.symbol.has_valid_operands() {
+            Ok(())
+        } else {
+            Err(EvalError::InvalidOperands {
+                offset: self.offset,
+                type_id: self.type_id,
+                count: self.children().len(),
+            })
         }
-        (out, offset)
     }
 
-    fn parse(bits_in: &BitSlice<Msb0, u8>) -> (Packet, usize) {
-        //println!("Parsing packet from bits {:?}", bits_in);
-        let mut count = 0;
-        let version = bits_in[count..count + 3].load_be::<u8>();
-        count += 3;
-        //println!("Found packet version {}", version);
-        let (symbol, size) = Symbol::parse(&bits_in[count..]);
-        count += size;
-        (Packet { version, symbol }, count)
+    /// Evaluate the packet, failing rather than overflowing a `u64`
+    pub fn eval_checked(&self) -> Result<u64, EvalError> {
+        self.check_operands()?;
+        let overflow = || EvalError::Overflow {
+            offset: self.offset,
+        };
+        let values = self
+            .children()
+            .iter()
+            .map(Packet::eval_checked)
+            .collect::<Result<Vec<u64>, EvalError>>()?;
+        let value = match &self.symbol {
+            Symbol::Literal(value) => *value,
+            Symbol::WideLiteral(_) => return Err(overflow()),
+            Symbol::OperatorSum(_) => values
+                .iter()
+                .try_fold(0u64, |sum, value| sum.checked_add(*value))
+                .ok_or_else(overflow)?,
+            Symbol::OperatorProduct(_) => values
+                .iter()
+                .try_fold(1u64, |product, value| product.checked_mul(*value))
+                .ok_or_else(overflow)?,
+            Symbol::OperatorMin(_) => values.into_iter().min().unwrap_or_default(),
+            Symbol::OperatorMax(_) => values.into_iter().max().unwrap_or_default(),
+            Symbol::OperatorGt(_) => (values[0] > values[1]) as u64,
+            Symbol::OperatorLt(_) => (values[0] < values[1]) as u64,
+            Symbol::OperatorEq(_) => (values[0] == values[1]) as u64,
+        };
+        trace_event!(Day16::DAY, Level::Trace, "packet evaluated", value = value);
+        Ok(value)
+    }
+
+    /// Evaluate the packet with integers of any size
+    pub fn eval_big(&self) -> Result<BigUint, EvalError> {
+        self.check_operands()?;
+        let values = self
+            .children()
+            .iter()
+            .map(Packet::eval_big)
+            .collect::<Result<Vec<BigUint>, EvalError>>()?;
+        let truth = |b: bool| BigUint::from(b as u64);
+        let value = match &self.symbol {
+            Symbol::Literal(value) => BigUint::from(*value),
+            Symbol::WideLiteral(value) => value.clone(),
+            Symbol::OperatorSum(_) => values
+                .iter()
+                .fold(BigUint::zero(), |sum, value| &sum + value),
+            Symbol::OperatorProduct(_) => values
+                .iter()
+                .fold(BigUint::from(1), |product, value| &product * value),
+            Symbol::OperatorMin(_) => values.into_iter().min().unwrap_or_default(),
+            Symbol::OperatorMax(_) => values.into_iter().max().unwrap_or_default(),
+            Symbol::OperatorGt(_) => truth(values[0] > values[1]),
+            Symbol::OperatorLt(_) => truth(values[0] < values[1]),
+            Symbol::OperatorEq(_) => truth(values[0] == values[1]),
+        };
+        trace_event!(Day16::DAY, Level::Trace, "packet evaluated", value = value);
+        Ok(value)
+    }
+
+    /// The packet and everything inside it, one packet per line and
+    /// indented by depth, with the version and range of bits of each, as in
+    /// `v1 < 0..49 length type 0`
+    pub fn tree(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(0, &mut out);
+        out
     }
 
-    fn eval(&self) -> u64 {
-        self.symbol.eval()
+    fn write_tree(&self, depth: usize, out: &mut String) {
+        let range = self.offset..self.offset + self.length;
+        let line = match (&self.symbol, self.length_type_id) {
+            (Symbol::Literal(value), _) => format!("v{} {} {:?}", self.version, value, range),
+            (Symbol::WideLiteral(value), _) => {
+                format!("v{} {} {:?}", self.version, value, range)
+            }
+            (symbol, length_type_id) => format!(
+                "v{} {} {:?} length type {}",
+                self.version,
+                symbol.name(),
+                range,
+                length_type_id.unwrap_or(0)
+            ),
+        };
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&line);
+        out.push('\n');
+        for child in self.children() {
+            child.write_tree(depth + 1, out);
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-enum Symbol {
+/// The expression the packet encodes, as an S-expression such as
+/// `(+ 1 (* 2 3) (max 7 8))`
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.symbol {
+            Symbol::Literal(value) => return write!(f, "{}", value),
+            Symbol::WideLiteral(value) => return write!(f, "{}", value),
+            _ => {}
+        }
+        write!(f, "({}", self.symbol.name())?;
+        for child in self.children() {
+            write!(f, " {}", child)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// What a packet holds: either a literal value, or an operator applied to
+/// its sub-packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol {
     Literal(u64),
+    /// A literal too large for a `u64`, only ever decoded with
+    /// [`Arithmetic::Big`]
+    WideLiteral(BigUint),
     OperatorSum(Vec<Packet>),
     OperatorProduct(Vec<Packet>),
     OperatorMin(Vec<Packet>),
@@ -79,253 +381,1163 @@ enum Symbol {
     OperatorEq(Vec<Packet>),
 }
 
+/// How many operators a packet can be nested inside, when decoding a
+/// transmission or parsing an expression, so that recursing into them
+/// can't overflow the stack
+pub const MAX_NESTING: usize = 256;
+
+/// The names operators go by in expressions, with their type IDs
+const OPERATORS: [(&str, u8); 7] = [
+    ("+", 0),
+    ("*", 1),
+    ("min", 2),
+    ("max", 3),
+    (">", 5),
+    ("<", 6),
+    ("=", 7),
+];
+
 impl Symbol {
-    fn parse(bits_in: &BitSlice<Msb0, u8>) -> (Self, usize) {
-        let mut count = 0;
-        let (type_id_bits, bits_in) = bits_in.split_at(3);
-        count += 3;
-        let type_id = type_id_bits.load_be::<u8>();
-        //println!("\tFound packet type {:#x}", type_id);
+    /// A literal holding `value`, as a `Literal` if it fits in one
+    pub fn literal(value: BigUint) -> Symbol {
+        match value.to_u64() {
+            Some(value) => Symbol::Literal(value),
+            None => Symbol::WideLiteral(value),
+        }
+    }
+
+    /// The operator with type ID `type_id` applied to `children`, unless
+    /// it's the type ID of a literal
+    pub fn operator(type_id: u8, children: Vec<Packet>) -> Option<Symbol> {
         match type_id {
-            0x4 => {
-                let (sym, len) = Symbol::parse_literal(bits_in);
-                (sym, count + len)
-            }
-            op => {
-                let (sym, len) = Symbol::parse_op(bits_in, op);
-                (sym, count + len)
-            }
+            0 => Some(Symbol::OperatorSum(children)),
+            1 => Some(Symbol::OperatorProduct(children)),
+            2 => Some(Symbol::OperatorMin(children)),
+            3 => Some(Symbol::OperatorMax(children)),
+            5 => Some(Symbol::OperatorGt(children)),
+            6 => Some(Symbol::OperatorLt(children)),
+            7 => Some(Symbol::OperatorEq(children)),
+            _ => None,
         }
     }
 
-    fn parse_literal(bits_in: &BitSlice<Msb0, u8>) -> (Self, usize) {
-        //println!("\tparse_literal({:?})", bits_in);
-        let (literal, count) = bits_in
-            .chunks_exact(5)
-            //.inspect(|b| println!("Parsing literal chunk {:b}", b))
-            .map(|b| b.load_be::<u8>())
-            //.inspect(|b| println!("Chunk became byte {:05b}", b))
-            .enumerate()
-            .scan((true, 0u64), |(do_next, acc), (count, x)| {
-                if *do_next {
-                    *do_next = (x & 0b010000) > 0;
-                    *acc = (*acc << 4) | (x & 0b01111) as u64;
-                    Some((*acc, (count + 1) * 5))
-                } else {
-                    None
-                }
-            })
-            //.inspect(|l| println!("Literal chunk became {:04b} [{} bits long]", l.0, l.1))
-            .last()
-            .unwrap();
+    /// The type ID of packets holding this kind of symbol
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Symbol::OperatorSum(_) => 0,
+            Symbol::OperatorProduct(_) => 1,
+            Symbol::OperatorMin(_) => 2,
+            Symbol::OperatorMax(_) => 3,
+            Symbol::Literal(_) | Symbol::WideLiteral(_) => 4,
+            Symbol::OperatorGt(_) => 5,
+            Symbol::OperatorLt(_) => 6,
+            Symbol::OperatorEq(_) => 7,
+        }
+    }
 
-        //println!("\tFound literal {:016b}, consuming {} bits", literal, count);
-        (Symbol::Literal(literal), count)
+    /// How the symbol is written in an expression
+    pub fn name(&self) -> &'static str {
+        let type_id = self.type_id();
+        OPERATORS
+            .iter()
+            .find(|(_, id)| *id == type_id)
+            .map_or("literal", |(name, _)| name)
     }
 
-    fn parse_op(bits_in: &BitSlice<Msb0, u8>, op: u8) -> (Self, usize) {
-        let mut count = 0;
+    /// Whether there are enough sub-packets to evaluate: at least one, or
+    /// exactly two for a comparison
+    pub fn has_valid_operands(&self) -> bool {
+        let count = self.children().len();
+        match self {
+            Symbol::Literal(_) | Symbol::WideLiteral(_) => true,
+            Symbol::OperatorGt(_) | Symbol::OperatorLt(_) | Symbol::OperatorEq(_) => count == 2,
+            _ => count > 0,
+        }
+    }
+
+    pub fn children(&self) -> &[Packet] {
+        match self {
+            Symbol::Literal(_) | Symbol::WideLiteral(_) => &[],
+            Symbol::OperatorSum(children)
+            | Symbol::OperatorProduct(children)
+            | Symbol::OperatorMin(children)
+            | Symbol::OperatorMax(children)
+            | Symbol::OperatorGt(children)
+            | Symbol::OperatorLt(children)
+            | Symbol::OperatorEq(children) => children,
+        }
+    }
+}
+
+/// Reads packets out of a transmission one field at a time, keeping track
+/// of how far in it is
+struct Decoder<'a> {
+    bits: &'a BitSlice<Msb0, u8>,
+    offset: usize,
+    /// Where the packets being read have to stop, which is short of the end
+    /// of the transmission inside an operator with a length in bits
+    end: usize,
+    /// Whether to reject transmissions that are well-formed enough to
+    /// decode but don't make sense
+    strict: bool,
+    /// What to do with literals that don't fit in a `u64`
+    literals: Arithmetic,
+    /// How many operators the packet being read is inside
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bits: &'a BitSlice<Msb0, u8>, strict: bool, literals: Arithmetic) -> Self {
+        Decoder {
+            bits,
+            offset: 0,
+            end: bits.len(),
+            strict,
+            literals,
+            depth: 0,
+        }
+    }
 
-        let length_type_id = bits_in[0];
-        count += 1;
+    /// Read the next `count` bits as a big-endian number
+    fn read(&mut self, count: usize, expected: &'static str) -> Result<usize, DecodeError> {
+        if self.offset + count > self.end {
+            return Err(DecodeError::Truncated {
+                offset: self.offset,
+                expected,
+            });
+        }
+        let value = self.bits[self.offset..self.offset + count].load_be::<usize>();
+        self.offset += count;
+        Ok(value)
+    }
 
-        let (out, count) = if length_type_id {
-            let packet_count_bits = &bits_in[count..count + 11];
-            count += 11;
-            let packet_count = packet_count_bits.load_be::<usize>();
-            //println!("\tPacket count: {} packets", packet_count);
-            let (out, more_count) = Packet::parse_group_n(&bits_in[count..], packet_count);
-            count += more_count;
-            //println!("\tFound op {} with {} children, consuming {} bits", op, out.len(), count);
-            (out, count)
+    fn packet(&mut self) -> Result<Packet, DecodeError> {
+        let offset = self.offset;
+        let version = self.read(3, "a packet version")? as u8;
+        let type_id = self.read(3, "a packet type ID")? as u8;
+        let (length_type_id, symbol) = if type_id == 4 {
+            (None, self.literal(offset)?)
         } else {
-            let payload_length_bits = &bits_in[count..count + 15];
-            //println!("\tPayload length bits: {:b}", payload_length_bits);
-            count += 15;
-            let payload_length = payload_length_bits.load_be::<usize>();
-            //println!("\tPayload length: {} bits", payload_length);
-            let (out, _more_count) = Packet::parse_group(&bits_in[count..count + payload_length]);
-            // TODO: this is probably off by one right now
-            count += payload_length;
-            // TODO: assert relationship between more_count and payload_length
-            //println!("\tFound op {} with {} children, consuming {} bits", op, out.len(), count);
-            (out, count)
+            let length_type_id = self.read(1, "a length type ID")? as u8;
+            if self.depth == MAX_NESTING {
+                return Err(DecodeError::TooDeep { offset });
+            }
+            self.depth += 1;
+            let symbol = self.operator(type_id, length_type_id)?;
+            self.depth -= 1;
+            if self.strict && !symbol.has_valid_operands() {
+                return Err(DecodeError::InvalidOperands {
+                    offset,
+                    type_id,
+                    count: symbol.children().len(),
+                });
+            }
+            (Some(length_type_id), symbol)
         };
+        let length = self.offset - offset;
+        trace_event!(
+            Day16::DAY,
+            Level::Debug,
+            "packet parsed",
+            version = version,
+            bits = length
+        );
+        Ok(Packet {
+            version,
+            type_id,
+            length_type_id,
+            offset,
+            length,
+            symbol,
+        })
+    }
 
-        match op {
-            0 => (Symbol::OperatorSum(out), count),
-            1 => (Symbol::OperatorProduct(out), count),
-            2 => (Symbol::OperatorMin(out), count),
-            3 => (Symbol::OperatorMax(out), count),
-            5 => (Symbol::OperatorGt(out), count),
-            6 => (Symbol::OperatorLt(out), count),
-            7 => (Symbol::OperatorEq(out), count),
-            _ => unreachable!(),
+    /// Read groups of four bits until one isn't prefixed by a one
+    fn literal(&mut self, offset: usize) -> Result<Symbol, DecodeError> {
+        let start = self.offset;
+        let mut literal = 0u64;
+        // Where the literal goes once it's too large for `literal`
+        let mut wide: Option<BigUint> = None;
+        loop {
+            let group = self.read(5, "a literal group")?;
+            let nibble = (group & 0b01111) as u64;
+            if let Some(wide) = wide.as_mut() {
+                *wide = &(&*wide * &BigUint::from(16)) + &BigUint::from(nibble);
+            } else if literal >> 60 == 0 || self.literals == Arithmetic::Wrapping {
+                literal = (literal << 4) | nibble;
+            } else if self.literals == Arithmetic::Big {
+                wide =
+                    Some(&(&BigUint::from(literal) * &BigUint::from(16)) + &BigUint::from(nibble));
+            } else {
+                return Err(DecodeError::LiteralTooLarge { offset });
+            }
+            if group & 0b10000 == 0 {
+                break;
+            }
         }
+        let symbol = match wide {
+            Some(wide) => Symbol::WideLiteral(wide),
+            None => Symbol::Literal(literal),
+        };
+        trace_event!(
+            Day16::DAY,
+            Level::Trace,
+            "literal parsed",
+            value = Packet::new(0, symbol.clone()),
+            bits = self.offset - start
+        );
+        Ok(symbol)
     }
 
-    fn children(&self) -> Vec<Packet> {
-        match self {
-            Symbol::Literal(_) => Vec::new(),
-            Symbol::OperatorSum(children) => children.clone(),
-            Symbol::OperatorProduct(children) => children.clone(),
-            Symbol::OperatorMin(children) => children.clone(),
-            Symbol::OperatorMax(children) => children.clone(),
-            Symbol::OperatorGt(children) => children.clone(),
-            Symbol::OperatorLt(children) => children.clone(),
-            Symbol::OperatorEq(children) => children.clone(),
-        }
-    }
-
-    fn eval(&self) -> u64 {
-        let val = match self {
-            Symbol::Literal(u) => *u as u64,
-            Symbol::OperatorSum(children) => children.iter().map(|p| p.eval()).sum(),
-            Symbol::OperatorProduct(children) => children
-                .iter()
-                .map(|p| p.eval())
-                .reduce(|accum, item| item * accum)
-                .unwrap(),
-            Symbol::OperatorMin(children) => children.iter().map(|p| p.eval()).min().unwrap(),
-            Symbol::OperatorMax(children) => children.iter().map(|p| p.eval()).max().unwrap(),
-            Symbol::OperatorGt(children) => (children[0].eval() > children[1].eval()) as u64,
-            Symbol::OperatorLt(children) => (children[0].eval() < children[1].eval()) as u64,
-            Symbol::OperatorEq(children) => (children[0].eval() == children[1].eval()) as u64,
+    fn operator(&mut self, type_id: u8, length_type_id: u8) -> Result<Symbol, DecodeError> {
+        let start = self.offset;
+        let children = if length_type_id == 1 {
+            let count = self.read(11, "a sub-packet count")?;
+            (0..count)
+                .map(|_| self.packet())
+                .collect::<Result<Vec<Packet>, DecodeError>>()?
+        } else {
+            let length = self.read(15, "a sub-packet length")?;
+            let end = self.offset + length;
+            if end > self.end {
+                return Err(DecodeError::Truncated {
+                    offset: self.offset,
+                    expected: "a sub-packet payload",
+                });
+            }
+            let mut children = Vec::with_capacity(2);
+            if self.strict {
+                // Let the last sub-packet run over, to find out by how much,
+                // but don't mistake leftover zeros for one
+                let payload = self.offset;
+                while self.offset < end && self.bits[self.offset..end].any() {
+                    children.push(self.packet()?);
+                }
+                if self.offset != end {
+                    return Err(DecodeError::PayloadMismatch {
+                        offset: payload,
+                        declared: length,
+                        consumed: self.offset - payload,
+                    });
+                }
+            } else {
+                let outer_end = std::mem::replace(&mut self.end, end);
+                while self.offset < end && self.bits[self.offset..end].any() {
+                    children.push(self.packet()?);
+                }
+                self.offset = end;
+                self.end = outer_end;
+            }
+            children
         };
+        trace_event!(
+            Day16::DAY,
+            Level::Trace,
+            "operator parsed",
+            type_id = type_id,
+            length_type = length_type_id,
+            children = children.len(),
+            bits = self.offset - start
+        );
 
-        //println!("eval({:?}) => {}", self, val);
-        val
+        Ok(Symbol::operator(type_id, children)
+            .expect("Type IDs are three bits, and 4 is a literal"))
     }
 }
 
-fn version_sum_packet(packet: &Packet) -> u32 {
-    let mut sum = packet.version as u32;
-    for child in packet.symbol.children() {
-        sum += version_sum_packet(&child) as u32;
-    }
-    sum
+/// Decode the outermost packet of a transmission. Anything after it is
+/// taken to be padding, zeros left over at the end of an operator's
+/// sub-packets are skipped, and literals keep their lowest 64 bits.
+/// Operators still can't be nested more than [`MAX_NESTING`] deep.
+pub fn decode(data: &[u8]) -> Result<Packet, DecodeError> {
+    decode_with(data, false, Arithmetic::Wrapping)
 }
 
-pub fn star1(data: &[u8]) -> u32 {
-    //println!("Scoring {:x?}", data);
+/// Decode the outermost packet of a transmission that can't be trusted to
+/// be well-formed, as [`decode_with`] does when strict, with literals that
+/// have to fit in 64 bits.
+pub fn decode_strict(data: &[u8]) -> Result<Packet, DecodeError> {
+    decode_with(data, true, Arithmetic::Checked)
+}
+
+/// Decode the outermost packet of a transmission, dealing with literals
+/// too large for a `u64` as `literals` says. If `strict`, this also checks
+/// that operators' sub-packets take up exactly the bits they're given and
+/// that there are enough of them to evaluate, and that only zeros follow
+/// the packet.
+pub fn decode_with(data: &[u8], strict: bool, literals: Arithmetic) -> Result<Packet, DecodeError> {
     let bits = BitSlice::<Msb0, u8>::from_slice(data).unwrap();
-    let (packets, _) = Packet::parse_group(bits);
-    let mut ver_sum: u32 = 0;
-    for packet in packets {
-        //println!("Packet: {:?}", packet);
-        ver_sum += version_sum_packet(&packet);
+    let mut decoder = Decoder::new(bits, strict, literals);
+    let packet = decoder.packet()?;
+    if let Some(one) = bits[decoder.offset..].first_one().filter(|_| strict) {
+        return Err(DecodeError::NonZeroPadding {
+            offset: decoder.offset + one,
+        });
     }
-    ver_sum
+    Ok(packet)
 }
 
-pub fn star2(data: &[u8]) -> u64 {
-    //println!("Scoring {:x?}", data);
-    let bits = BitSlice::<Msb0, u8>::from_slice(data).unwrap();
-    let (packets, _) = Packet::parse_group(bits);
-    if let Some(packet) = packets.into_iter().next() {
-        packet.eval()
-    } else {
-        unreachable!();
+/// Why a packet tree couldn't be encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// A version or length type ID too large for its field
+    InvalidField { field: &'static str, value: u8 },
+    /// An operator's sub-packets take up too many bits, or are too many, to
+    /// give with its length type
+    PayloadTooLarge { length_type_id: u8, size: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::InvalidField { field, value } => {
+                write!(f, "{} {} doesn't fit in a packet", field, value)
+            }
+            EncodeError::PayloadTooLarge {
+                length_type_id,
+                size,
+            } => write!(
+                f,
+                "Sub-packet {} {} doesn't fit in length type {}",
+                if *length_type_id == 0 {
+                    "length"
+                } else {
+                    "count"
+                },
+                size,
+                length_type_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// How to lay packets out when encoding them. The default lays them out
+/// just as they were decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Encoding {
+    /// The length type ID to give every operator, instead of its own
+    pub length_type_id: Option<u8>,
+    /// The number of groups to write every literal in, instead of as many
+    /// as it was decoded from. Literals that need more groups get them.
+    pub literal_groups: Option<usize>,
+}
+
+fn write_bits(bits: &mut BitVec<Msb0, u8>, value: u64, width: usize) {
+    for i in (0..width).rev() {
+        bits.push((value >> i) & 1 == 1);
+    }
+}
+
+fn encode_packet(
+    packet: &Packet,
+    encoding: &Encoding,
+    bits: &mut BitVec<Msb0, u8>,
+) -> Result<(), EncodeError> {
+    if packet.version >= 8 {
+        return Err(EncodeError::InvalidField {
+            field: "Version",
+            value: packet.version,
+        });
+    }
+    write_bits(bits, packet.version as u64, 3);
+    write_bits(bits, packet.symbol.type_id() as u64, 3);
+
+    let value = match &packet.symbol {
+        Symbol::Literal(value) => Some(BigUint::from(*value)),
+        Symbol::WideLiteral(value) => Some(value.clone()),
+        _ => None,
+    };
+    if let Some(value) = value {
+        let needed = value.bits().max(1).div_ceil(4);
+        let groups = encoding
+            .literal_groups
+            .unwrap_or_else(|| packet.length.saturating_sub(6) / 5)
+            .max(needed);
+        for group in (0..groups).rev() {
+            write_bits(bits, (group > 0) as u64, 1);
+            for bit in (group * 4..group * 4 + 4).rev() {
+                bits.push(value.bit(bit));
+            }
+        }
+        return Ok(());
+    }
+
+    let length_type_id = encoding
+        .length_type_id
+        .or(packet.length_type_id)
+        .unwrap_or(0);
+    let children = packet.children();
+    match length_type_id {
+        0 => {
+            let mut payload = BitVec::new();
+            for child in children {
+                encode_packet(child, encoding, &mut payload)?;
+            }
+            if payload.len() >= 1 << 15 {
+                return Err(EncodeError::PayloadTooLarge {
+                    length_type_id,
+                    size: payload.len(),
+                });
+            }
+            write_bits(bits, 0, 1);
+            write_bits(bits, payload.len() as u64, 15);
+            bits.extend_from_bitslice(&payload);
+        }
+        1 => {
+            if children.len() >= 1 << 11 {
+                return Err(EncodeError::PayloadTooLarge {
+                    length_type_id,
+                    size: children.len(),
+                });
+            }
+            write_bits(bits, 1, 1);
+            write_bits(bits, children.len() as u64, 11);
+            for child in children {
+                encode_packet(child, encoding, bits)?;
+            }
+        }
+        _ => {
+            return Err(EncodeError::InvalidField {
+                field: "Length type ID",
+                value: length_type_id,
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Lay a packet and everything inside it out as bits
+pub fn encode(packet: &Packet, encoding: &Encoding) -> Result<BitVec<Msb0, u8>, EncodeError> {
+    let mut bits = BitVec::with_capacity(packet.length);
+    encode_packet(packet, encoding, &mut bits)?;
+    Ok(bits)
+}
+
+/// Encode a packet as a hexadecimal transmission, padded with zeros to a
+/// whole number of bytes
+pub fn encode_hex(packet: &Packet, encoding: &Encoding) -> Result<String, EncodeError> {
+    let mut bits = encode(packet, encoding)?;
+    bits.resize(bits.len().div_ceil(8) * 8, false);
+    Ok(bits
+        .into_vec()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect())
+}
+
+/// Consume any spaces, returning whether there were some
+fn skip_spaces(parser: &mut LineParser) -> bool {
+    let mut skipped = false;
+    while parser.accept(" ") {
+        skipped = true;
+    }
+    skipped
+}
+
+/// Parse an operand inside `depth` operators
+fn expression(parser: &mut LineParser, depth: usize) -> Result<Packet, ParseError> {
+    if depth == MAX_NESTING && parser.peek() == Some('(') {
+        return Err(parser.error(format!(
+            "a literal, as operators nest at most {} deep",
+            MAX_NESTING
+        )));
+    }
+    if !parser.accept("(") {
+        return Ok(Packet::new(0, Symbol::literal(parser.integer()?)));
+    }
+    let mut type_id = None;
+    for (name, id) in OPERATORS {
+        if parser.accept(name) {
+            type_id = Some(id);
+            break;
+        }
+    }
+    let type_id = type_id.ok_or_else(|| parser.error("an operator"))?;
+    let comparison = type_id >= 5;
+
+    let mut children = Vec::with_capacity(2);
+    loop {
+        let spaced = skip_spaces(parser);
+        let complete = if comparison {
+            children.len() == 2
+        } else {
+            !children.is_empty() && parser.peek() == Some(')')
+        };
+        if complete {
+            parser.expect(")")?;
+            break;
+        }
+        if !spaced {
+            return Err(parser.error("\" \" and an operand"));
+        }
+        children.push(expression(parser, depth + 1)?);
+    }
+    Ok(Packet::new(
+        0,
+        Symbol::operator(type_id, children).expect("Operators aren't literals"),
+    ))
+}
+
+/// Parse an expression written the way packets are displayed, such as
+/// `(+ 1 (* 2 3) (max 7 8))`, into packets with version 0. Comparisons
+/// take exactly two operands, other operators at least one, and operators
+/// nest at most [`MAX_NESTING`] deep.
+pub fn parse_expression(text: &str) -> Result<Packet, ParseError> {
+    let mut parser = LineParser::new(1, text);
+    skip_spaces(&mut parser);
+    let packet = expression(&mut parser, 0)?;
+    parser.end()?;
+    Ok(packet)
+}
+
+/// Compile an expression into a hexadecimal transmission
+pub fn compile(text: &str) -> Result<String> {
+    Ok(encode_hex(&parse_expression(text)?, &Encoding::default())?)
+}
+
+pub fn star1(data: &[u8]) -> Result<u32> {
+    // Versions don't depend on literals' values, so let those wrap around
+    let sum = decode_with(data, true, Arithmetic::Wrapping)?.version_sum();
+    trace_event!(Day16::DAY, Level::Info, "versions summed", sum = sum);
+    Ok(sum)
+}
+
+pub fn star2(data: &[u8]) -> Result<u64> {
+    let value = decode_strict(data)?.eval_checked()?;
+    trace_event!(
+        Day16::DAY,
+        Level::Info,
+        "transmission evaluated",
+        value = value
+    );
+    Ok(value)
+}
+
+pub struct Day16;
+
+impl Puzzle for Day16 {
+    const DAY: u8 = 16;
+    type Input = Vec<u8>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::Found;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_str("D2FE28").unwrap(), vec![0xD2, 0xFE, 0x28]);
+
+        let err = parse_str("D2FE2").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (1, 6));
+
+        let err = parse_str("D2FG28").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(
+            (err.column, err.expected.as_str()),
+            (4, "a hexadecimal digit")
+        );
+
+        assert!(parse_str("").is_err());
+
+        // No truncation of a transmission may panic
+        let sample = SAMPLE_DATA_7.0[0];
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let packet = decode(&parse_str("D2FE28").unwrap()).unwrap();
+        assert_eq!(
+            packet,
+            Packet {
+                version: 6,
+                type_id: 4,
+                length_type_id: None,
+                offset: 0,
+                length: 21,
+                symbol: Symbol::Literal(2021),
+            }
+        );
+
+        let packet = decode(&parse_str("38006F45291200").unwrap()).unwrap();
+        assert_eq!(
+            (packet.type_id, packet.length_type_id, packet.length),
+            (6, Some(0), 49)
+        );
+        let children: Vec<(usize, usize)> = packet
+            .children()
+            .iter()
+            .map(|p| (p.offset, p.length))
+            .collect();
+        assert_eq!(children, [(22, 11), (33, 16)]);
+        assert_eq!(packet.children()[1].symbol, Symbol::Literal(20));
+
+        let packet = decode(&parse_str("EE00D40C823060").unwrap()).unwrap();
+        assert_eq!((packet.type_id, packet.length_type_id), (3, Some(1)));
+        let children: Vec<(usize, &Symbol)> = packet
+            .children()
+            .iter()
+            .map(|p| (p.offset, &p.symbol))
+            .collect();
+        assert_eq!(
+            children,
+            [
+                (18, &Symbol::Literal(1)),
+                (29, &Symbol::Literal(2)),
+                (40, &Symbol::Literal(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode(&[]),
+            Err(DecodeError::Truncated {
+                offset: 0,
+                expected: "a packet version"
+            })
+        );
+        assert_eq!(
+            decode(&[0xD2, 0xFE]),
+            Err(DecodeError::Truncated {
+                offset: 16,
+                expected: "a literal group"
+            })
+        );
+        assert_eq!(
+            decode(&[0x38, 0x00, 0x6F]),
+            Err(DecodeError::Truncated {
+                offset: 22,
+                expected: "a sub-packet payload"
+            })
+        );
+
+        // No truncation of a transmission may panic
+        let sample = parse_str(SAMPLE_DATA_7.0[0]).unwrap();
+        for end in 0..sample.len() {
+            assert!(decode(&sample[..end]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_strict() {
+        for sample in [SAMPLE_DATA_4.0, SAMPLE_DATA_7.0, SAMPLE_DATA_H.0] {
+            let data = parse_str(sample[0]).unwrap();
+            assert_eq!(decode_strict(&data), decode(&data));
+        }
+
+        // (< 10 20) with room for 26 bits of sub-packets instead of 27
+        let mut bits = encode(
+            &Packet::new(1, Symbol::OperatorLt(vec![literal(6, 10), literal(2, 20)])),
+            &Encoding::default(),
+        )
+        .unwrap();
+        bits[7..22].store_be(26usize);
+        assert_eq!(
+            decode_strict(bits.as_raw_slice()),
+            Err(DecodeError::PayloadMismatch {
+                offset: 22,
+                declared: 26,
+                consumed: 27
+            })
+        );
+
+        // Zeros left over after the sub-packets
+        let sum = Packet::new(0, Symbol::OperatorSum(vec![literal(0, 1)]));
+        let mut bits = encode(&sum, &Encoding::default()).unwrap();
+        bits[7..22].store_be(11 + 12usize);
+        bits.resize(22 + 11 + 12, false);
+        assert_eq!(decode(bits.as_raw_slice()).unwrap().eval(), Ok(1));
+        assert!(matches!(
+            decode_strict(bits.as_raw_slice()),
+            Err(DecodeError::PayloadMismatch { offset: 22, .. })
+        ));
+
+        assert_eq!(
+            decode_strict(&[0xD2, 0xFE, 0x28, 0x01]),
+            Err(DecodeError::NonZeroPadding { offset: 31 })
+        );
+        assert!(decode(&[0xD2, 0xFE, 0x28, 0x01]).is_ok());
+
+        // A seventeenth group with a one in its first nibble
+        let wide = Encoding {
+            literal_groups: Some(17),
+            ..Encoding::default()
+        };
+        let mut bits = encode(&literal(0, u64::MAX), &wide).unwrap();
+        assert!(decode_strict(bits.as_raw_slice()).is_ok());
+        bits.set(10, true);
+        assert_eq!(
+            decode_strict(bits.as_raw_slice()),
+            Err(DecodeError::LiteralTooLarge { offset: 0 })
+        );
+        assert_eq!(decode(bits.as_raw_slice()).unwrap().eval(), Ok(u64::MAX));
+
+        let gt = Packet::new(0, Symbol::OperatorGt(vec![literal(0, 1)]));
+        let empty = Packet::new(0, Symbol::OperatorMin(vec![]));
+        for (packet, type_id, count) in [(&gt, 5, 1), (&empty, 2, 0)] {
+            let bits = encode(packet, &Encoding::default()).unwrap();
+            assert_eq!(
+                decode_strict(bits.as_raw_slice()),
+                Err(DecodeError::InvalidOperands {
+                    offset: 0,
+                    type_id,
+                    count
+                })
+            );
+        }
+
+        // Operators nested as deep as they can go, then one deeper
+        let mut deep = literal(0, 1);
+        for _ in 0..MAX_NESTING {
+            deep = Packet::new(0, Symbol::OperatorSum(vec![deep]));
+        }
+        let bits = encode(&deep, &Encoding::default()).unwrap();
+        assert_eq!(decode_strict(bits.as_raw_slice()).unwrap().eval(), Ok(1));
+        let deeper = Packet::new(0, Symbol::OperatorMax(vec![deep]));
+        let bits = encode(&deeper, &Encoding::default()).unwrap();
+        let too_deep = DecodeError::TooDeep {
+            offset: MAX_NESTING * 22,
+        };
+        assert_eq!(decode_strict(bits.as_raw_slice()), Err(too_deep.clone()));
+        assert_eq!(decode(bits.as_raw_slice()), Err(too_deep));
+
+        // Every truncation is an error rather than a panic
+        let sample = parse_str(SAMPLE_DATA_7.0[0]).unwrap();
+        for end in 0..sample.len() {
+            assert!(decode_strict(&sample[..end]).is_err());
+        }
+        let err = star2(&[0x38, 0x00, 0x6F]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Transmission ended at bit 22, expected a sub-packet payload"
+        );
+    }
+
+    #[test]
+    fn test_print() {
+        let packet = decode(&parse_str(SAMPLE_DATA_H.0[0]).unwrap()).unwrap();
+        assert_eq!(packet.to_string(), "(= (+ 1 3) (* 2 2))");
+
+        let packet = decode(&parse_str(SAMPLE_DATA_2.0[0]).unwrap()).unwrap();
+        assert_eq!(
+            packet.tree(),
+            "v1 < 0..49 length type 0\n  v6 10 22..33\n  v2 20 33..49\n"
+        );
+        let packet = decode(&parse_str(SAMPLE_DATA_4.0[0]).unwrap()).unwrap();
+        assert_eq!(
+            packet.tree().lines().collect::<Vec<&str>>(),
+            [
+                "v4 min 0..69 length type 1",
+                "  v1 min 18..69 length type 1",
+                "    v5 min 36..69 length type 0",
+                "      v6 15 58..69",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let packet = parse_expression("(+ 1 (* 2 3) (max 7 8))").unwrap();
+        assert_eq!(packet.to_string(), "(+ 1 (* 2 3) (max 7 8))");
+        assert_eq!(packet.eval(), Ok(15));
+        assert_eq!(
+            parse_expression("  (min  (< 5  9)   0)")
+                .unwrap()
+                .to_string(),
+            "(min (< 5 9) 0)"
+        );
+
+        let hex = compile("(+ 1 (* 2 3) (max 7 8))").unwrap();
+        assert_eq!(star2(&parse_str(&hex).unwrap()).unwrap(), 15);
+        assert_eq!(compile("2021").unwrap(), "12FE28");
+
+        // Printing and parsing again keeps the meaning of every sample
+        for sample in [SAMPLE_DATA_5.0, SAMPLE_DATA_C.0, SAMPLE_DATA_H.0] {
+            let packet = decode(&parse_str(sample[0]).unwrap()).unwrap();
+            let parsed = parse_expression(&packet.to_string()).unwrap();
+            assert_eq!(parsed.to_string(), packet.to_string());
+            assert_eq!(parsed.eval(), packet.eval());
+        }
+
+        for (text, column) in [
+            ("(+)", 3),
+            ("(+ )", 4),
+            ("(+1 2)", 3),
+            ("(< 1 2 3)", 8),
+            ("(> 1)", 5),
+            ("(foo 1)", 2),
+            ("(+ 1", 5),
+            ("(+ 1) 2", 6),
+            ("(+ -1)", 4),
+            ("", 1),
+        ] {
+            let err = parse_expression(text).unwrap_err();
+            assert_eq!(err.column, column, "{:?}: {}", text, err);
+        }
+
+        let nested = |depth| format!("{}1{}", "(+ ".repeat(depth), ")".repeat(depth));
+        let packet = parse_expression(&nested(MAX_NESTING)).unwrap();
+        assert_eq!(packet.eval(), Ok(1));
+        let err = parse_expression(&nested(MAX_NESTING + 1)).unwrap_err();
+        assert_eq!(
+            (err.column, err.found),
+            (3 * MAX_NESTING + 1, Found::Char('('))
+        );
+    }
+
+    #[test]
+    fn test_eval_overflow() {
+        let data = parse_str(&compile("(* 4294967296 4294967296 3)").unwrap()).unwrap();
+        let packet = decode_strict(&data).unwrap();
+        assert_eq!(packet.eval(), Ok(0));
+        assert_eq!(
+            packet.eval_checked(),
+            Err(EvalError::Overflow { offset: 0 })
+        );
+        assert_eq!(
+            packet.eval_big().unwrap().to_string(),
+            "55340232221128654848"
+        );
+        assert_eq!(
+            star2(&data).unwrap_err().to_string(),
+            "Packet at bit 0 overflowed 64 bits"
+        );
+
+        let packet = parse_expression("(+ 1 (> 3 (+ 18446744073709551615 1)))").unwrap();
+        assert_eq!(packet.eval(), Ok(2));
+        assert!(packet.eval_checked().is_err());
+        assert_eq!(packet.eval_big().unwrap().to_string(), "1");
+
+        let packet = parse_expression("(min 7 (max 2 5))").unwrap();
+        assert_eq!(packet.eval_checked(), Ok(5));
+        assert_eq!(packet.eval_big().unwrap().to_u64(), Some(5));
+
+        let gt = Packet::new(0, Symbol::OperatorGt(vec![literal(0, 1)]));
+        let expected = EvalError::InvalidOperands {
+            offset: 0,
+            type_id: 5,
+            count: 1,
+        };
+        assert_eq!(gt.eval(), Err(expected.clone()));
+        assert_eq!(gt.eval_checked(), Err(expected.clone()));
+        assert_eq!(gt.eval_big(), Err(expected));
+
+        // Non-strict decoding lets through operators with no sub-packets
+        let min = Packet::new(0, Symbol::OperatorMin(vec![]));
+        let decoded = decode(&encode(&min, &Encoding::default()).unwrap().into_vec()).unwrap();
+        let expected = EvalError::InvalidOperands {
+            offset: 0,
+            type_id: 2,
+            count: 0,
+        };
+        assert_eq!(decoded.eval(), Err(expected));
+    }
+
+    #[test]
+    fn test_wide_literals() {
+        // 2^64 + 5, which takes seventeen groups
+        let text = "18446744073709551621";
+        let packet = parse_expression(text).unwrap();
+        assert!(matches!(packet.symbol, Symbol::WideLiteral(_)));
+        assert_eq!(packet.to_string(), text);
+        let bits = encode(&packet, &Encoding::default()).unwrap();
+        assert_eq!(bits.len(), 6 + 17 * 5);
+
+        let data = bits.as_raw_slice();
+        assert_eq!(
+            decode_with(data, false, Arithmetic::Checked),
+            Err(DecodeError::LiteralTooLarge { offset: 0 })
+        );
+        assert_eq!(decode(data).unwrap().symbol, Symbol::Literal(5));
+        let big = decode_with(data, true, Arithmetic::Big).unwrap();
+        assert_eq!((big.offset, big.length), (0, 91));
+        assert_eq!(big.eval_big().unwrap().to_string(), text);
+        assert_eq!(big.eval(), Ok(5));
+        assert_eq!(big.eval_checked(), Err(EvalError::Overflow { offset: 0 }));
+        assert_eq!(
+            encode_hex(&big, &Encoding::default()).unwrap(),
+            encode_hex(&packet, &Encoding::default()).unwrap()
+        );
+
+        let sum = parse_expression(&format!("(+ {} {})", text, text)).unwrap();
+        let data = parse_str(&encode_hex(&sum, &Encoding::default()).unwrap()).unwrap();
+        let decoded = decode_with(&data, true, Arithmetic::Big).unwrap();
+        assert_eq!(decoded.to_string(), sum.to_string());
+        assert_eq!(
+            decoded.eval_big().unwrap().to_string(),
+            "36893488147419103242"
+        );
+        assert_eq!(decoded.eval(), Ok(10));
+        assert!(decode_strict(&data).is_err());
+
+        // Leading zero groups don't count against a literal's size
+        let padded = Encoding {
+            literal_groups: Some(20),
+            ..Encoding::default()
+        };
+        let bits = encode(&literal(0, u64::MAX), &padded).unwrap();
+        let packet = decode_with(bits.as_raw_slice(), true, Arithmetic::Big).unwrap();
+        assert_eq!(packet.symbol, Symbol::Literal(u64::MAX));
+    }
+
+    fn literal(version: u8, value: u64) -> Packet {
+        Packet::new(version, Symbol::Literal(value))
+    }
+
+    #[test]
+    fn test_encode() {
+        let encoding = Encoding::default();
+        assert_eq!(encode_hex(&literal(6, 2021), &encoding).unwrap(), "D2FE28");
+        assert_eq!(encode(&literal(6, 2021), &encoding).unwrap().len(), 21);
+
+        // (< 10 20), by length in bits then by count of packets
+        let lt = Packet::new(1, Symbol::OperatorLt(vec![literal(6, 10), literal(2, 20)]));
+        assert_eq!(encode_hex(&lt, &encoding).unwrap(), "38006F45291200");
+        let by_count = Encoding {
+            length_type_id: Some(1),
+            ..Encoding::default()
+        };
+        let packet = decode(&parse_str(&encode_hex(&lt, &by_count).unwrap()).unwrap()).unwrap();
+        assert_eq!(packet.length_type_id, Some(1));
+        assert_eq!(packet.length, 6 + 1 + 11 + 11 + 16);
+        assert_eq!(packet.eval(), Ok(1));
+
+        // Literals can be padded out with leading zero groups
+        let padded = Encoding {
+            literal_groups: Some(3),
+            ..Encoding::default()
+        };
+        let bits = encode(&literal(0, 1), &padded).unwrap();
+        assert_eq!(bits.len(), 6 + 3 * 5);
+        let packet = decode(bits.as_raw_slice()).unwrap();
+        assert_eq!((packet.length, packet.eval()), (21, Ok(1)));
+        assert_eq!(
+            encode(&literal(0, u64::MAX), &padded).unwrap().len(),
+            6 + 16 * 5
+        );
+        let wide = Encoding {
+            literal_groups: Some(20),
+            ..Encoding::default()
+        };
+        let bits = encode(&literal(0, u64::MAX), &wide).unwrap();
+        assert_eq!(decode(bits.as_raw_slice()).unwrap().eval(), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let encoding = Encoding::default();
+        assert_eq!(
+            encode(&literal(8, 1), &encoding),
+            Err(EncodeError::InvalidField {
+                field: "Version",
+                value: 8
+            })
+        );
+        let sum = Packet::new(0, Symbol::OperatorSum(vec![literal(0, 1); 3000]));
+        assert_eq!(
+            encode(&sum, &encoding),
+            Err(EncodeError::PayloadTooLarge {
+                length_type_id: 0,
+                size: 3000 * 11
+            })
+        );
+        let by_count = Encoding {
+            length_type_id: Some(1),
+            ..Encoding::default()
+        };
+        assert_eq!(
+            encode(&sum, &by_count),
+            Err(EncodeError::PayloadTooLarge {
+                length_type_id: 1,
+                size: 3000
+            })
+        );
+        let invalid = Encoding {
+            length_type_id: Some(2),
+            ..Encoding::default()
+        };
+        assert!(encode(&sum, &invalid).is_err());
+    }
 
-    const SAMPLE_DATA_1: ([&'static str; 1], u32) = (["D2FE28"], 6);
+    /// Decoding a transmission and encoding it again gives back the same
+    /// transmission, save for any extra padding
+    fn assert_round_trip(hex: &str) {
+        let packet = decode(&parse_str(hex).unwrap()).unwrap();
+        let encoded = encode_hex(&packet, &Encoding::default()).unwrap();
+        assert!(
+            hex.starts_with(&encoded) && hex[encoded.len()..].chars().all(|c| c == '0'),
+            "{} became {}",
+            hex,
+            encoded
+        );
+        assert_eq!(decode(&parse_str(&encoded).unwrap()).unwrap(), packet);
+    }
 
-    const SAMPLE_DATA_2: ([&'static str; 1], u32) = (["38006F45291200"], 9);
+    #[test]
+    fn test_round_trip() {
+        let samples = [
+            SAMPLE_DATA_1.0,
+            SAMPLE_DATA_2.0,
+            SAMPLE_DATA_3.0,
+            SAMPLE_DATA_4.0,
+            SAMPLE_DATA_5.0,
+            SAMPLE_DATA_6.0,
+            SAMPLE_DATA_7.0,
+            SAMPLE_DATA_A.0,
+            SAMPLE_DATA_B.0,
+            SAMPLE_DATA_C.0,
+            SAMPLE_DATA_D.0,
+            SAMPLE_DATA_E.0,
+            SAMPLE_DATA_F.0,
+            SAMPLE_DATA_G.0,
+            SAMPLE_DATA_H.0,
+        ];
+        for sample in samples {
+            assert_round_trip(sample[0]);
+        }
 
-    const SAMPLE_DATA_3: ([&'static str; 1], u32) = (["EE00D40C823060"], 14);
+        for seed in 0..50 {
+            let transmission = crate::generate::day16(seed, 1 + seed as usize * 3);
+            assert_round_trip(transmission.trim_end());
 
-    const SAMPLE_DATA_4: ([&'static str; 1], u32) = (["8A004A801A8002F478"], 16);
+            // Laying the packets out differently keeps what they mean
+            let packet = decode(&parse_str(&transmission).unwrap()).unwrap();
+            for length_type_id in [0, 1] {
+                let encoding = Encoding {
+                    length_type_id: Some(length_type_id),
+                    literal_groups: Some(seed as usize % 4),
+                };
+                let bits = encode(&packet, &encoding).unwrap();
+                let recoded = decode(bits.as_raw_slice()).unwrap();
+                assert_eq!(recoded.version_sum(), packet.version_sum(), "seed {}", seed);
+                assert_eq!(recoded.eval(), packet.eval(), "seed {}", seed);
+            }
+        }
+    }
 
-    const SAMPLE_DATA_5: ([&'static str; 1], u32) = (["620080001611562C8802118E34"], 12);
+    const SAMPLE_DATA_1: ([&str; 1], u32) = (["D2FE28"], 6);
 
-    const SAMPLE_DATA_6: ([&'static str; 1], u32) = (["C0015000016115A2E0802F182340"], 23);
+    const SAMPLE_DATA_2: ([&str; 1], u32) = (["38006F45291200"], 9);
 
-    const SAMPLE_DATA_7: ([&'static str; 1], u32) = (["A0016C880162017C3686B18A3D4780"], 31);
+    const SAMPLE_DATA_3: ([&str; 1], u32) = (["EE00D40C823060"], 14);
+
+    const SAMPLE_DATA_4: ([&str; 1], u32) = (["8A004A801A8002F478"], 16);
+
+    const SAMPLE_DATA_5: ([&str; 1], u32) = (["620080001611562C8802118E34"], 12);
+
+    const SAMPLE_DATA_6: ([&str; 1], u32) = (["C0015000016115A2E0802F182340"], 23);
+
+    const SAMPLE_DATA_7: ([&str; 1], u32) = (["A0016C880162017C3686B18A3D4780"], 31);
 
     #[test]
     fn test_star1() {
-        //println!("\n==== Test 1 ====");
-        let data = parse(SAMPLE_DATA_1.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_1.1);
+        let data = parse_str(&SAMPLE_DATA_1.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_1.1);
 
-        //println!("\n==== Test 2 ====");
-        let data = parse(SAMPLE_DATA_2.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_2.1);
+        let data = parse_str(&SAMPLE_DATA_2.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_2.1);
 
-        //println!("\n==== Test 3 ====");
-        let data = parse(SAMPLE_DATA_3.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_3.1);
+        let data = parse_str(&SAMPLE_DATA_3.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_3.1);
 
-        //println!("\n==== Test 4 ====");
-        let data = parse(SAMPLE_DATA_4.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_4.1);
+        let data = parse_str(&SAMPLE_DATA_4.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_4.1);
 
-        //println!("\n==== Test 5 ====");
-        let data = parse(SAMPLE_DATA_5.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_5.1);
+        let data = parse_str(&SAMPLE_DATA_5.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_5.1);
 
-        //println!("\n==== Test 6 ====");
-        let data = parse(SAMPLE_DATA_6.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_6.1);
+        // A literal too wide to evaluate still has a version
+        let wide = Packet::new(5, Symbol::literal("18446744073709551621".parse().unwrap()));
+        let data = parse_str(&encode_hex(&wide, &Encoding::default()).unwrap()).unwrap();
+        assert_eq!(star1(&data).unwrap(), 5);
+        assert!(star2(&data).is_err());
 
-        //println!("\n==== Test 7 ====");
-        let data = parse(SAMPLE_DATA_7.0.iter().map(|r| r.to_string()));
-        assert_eq!(star1(&data), SAMPLE_DATA_7.1);
+        let data = parse_str(&SAMPLE_DATA_6.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_6.1);
+
+        let data = parse_str(&SAMPLE_DATA_7.0.join("\n")).unwrap();
+        assert_eq!(star1(&data).unwrap(), SAMPLE_DATA_7.1);
     }
 
-    const SAMPLE_DATA_A: ([&'static str; 1], u64) = (["C200B40A82"], 3);
+    const SAMPLE_DATA_A: ([&str; 1], u64) = (["C200B40A82"], 3);
 
-    const SAMPLE_DATA_B: ([&'static str; 1], u64) = (["04005AC33890"], 54);
+    const SAMPLE_DATA_B: ([&str; 1], u64) = (["04005AC33890"], 54);
 
-    const SAMPLE_DATA_C: ([&'static str; 1], u64) = (["880086C3E88112"], 7);
+    const SAMPLE_DATA_C: ([&str; 1], u64) = (["880086C3E88112"], 7);
 
-    const SAMPLE_DATA_D: ([&'static str; 1], u64) = (["CE00C43D881120"], 9);
+    const SAMPLE_DATA_D: ([&str; 1], u64) = (["CE00C43D881120"], 9);
 
-    const SAMPLE_DATA_E: ([&'static str; 1], u64) = (["D8005AC2A8F0"], 1);
+    const SAMPLE_DATA_E: ([&str; 1], u64) = (["D8005AC2A8F0"], 1);
 
-    const SAMPLE_DATA_F: ([&'static str; 1], u64) = (["F600BC2D8F"], 0);
+    const SAMPLE_DATA_F: ([&str; 1], u64) = (["F600BC2D8F"], 0);
 
-    const SAMPLE_DATA_G: ([&'static str; 1], u64) = (["9C005AC2F8F0"], 0);
+    const SAMPLE_DATA_G: ([&str; 1], u64) = (["9C005AC2F8F0"], 0);
 
-    const SAMPLE_DATA_H: ([&'static str; 1], u64) = (["9C0141080250320F1802104A08"], 1);
+    const SAMPLE_DATA_H: ([&str; 1], u64) = (["9C0141080250320F1802104A08"], 1);
 
     #[test]
     fn test_star2() {
-        //println!("\n==== Test A ====");
-        let data = parse(SAMPLE_DATA_A.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_A.1);
+        let data = parse_str(&SAMPLE_DATA_A.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_A.1);
+
+        let data = parse_str(&SAMPLE_DATA_B.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_B.1);
 
-        //println!("\n==== Test B ====");
-        let data = parse(SAMPLE_DATA_B.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_B.1);
+        let data = parse_str(&SAMPLE_DATA_C.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_C.1);
 
-        //println!("\n==== Test C ====");
-        let data = parse(SAMPLE_DATA_C.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_C.1);
+        let data = parse_str(&SAMPLE_DATA_D.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_D.1);
 
-        //println!("\n==== Test D ====");
-        let data = parse(SAMPLE_DATA_D.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_D.1);
+        let data = parse_str(&SAMPLE_DATA_E.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_E.1);
 
-        //println!("\n==== Test E ====");
-        let data = parse(SAMPLE_DATA_E.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_E.1);
+        let data = parse_str(&SAMPLE_DATA_F.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_F.1);
 
-        //println!("\n==== Test F ====");
-        let data = parse(SAMPLE_DATA_F.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_F.1);
+        let data = parse_str(&SAMPLE_DATA_G.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_G.1);
 
-        //println!("\n==== Test G ====");
-        let data = parse(SAMPLE_DATA_G.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_G.1);
+        let data = parse_str(&SAMPLE_DATA_H.0.join("\n")).unwrap();
+        assert_eq!(star2(&data).unwrap(), SAMPLE_DATA_H.1);
+    }
 
-        //println!("\n==== Test H ====");
-        let data = parse(SAMPLE_DATA_H.0.iter().map(|r| r.to_string()));
-        assert_eq!(star2(&data), SAMPLE_DATA_H.1);
+    #[test]
+    fn test_trace() {
+        let data = parse_str(&SAMPLE_DATA_4.0.join("\n")).unwrap();
+        let (sum, events) =
+            crate::trace::capture(Day16::DAY, Level::Debug, || star1(&data).unwrap());
+        let versions: Vec<&str> = events
+            .iter()
+            .filter(|e| e.name == "packet parsed")
+            .map(|e| e.field("version").unwrap())
+            .collect();
+        assert_eq!(versions, ["6", "5", "1", "4"]);
+        assert_eq!(
+            events.last().unwrap().field("sum"),
+            Some(sum.to_string().as_str())
+        );
+        assert!(events.iter().all(|e| e.level <= Level::Debug));
     }
 }
@@ -1,49 +1,63 @@
+use anyhow::Result;
 use std::io::BufRead;
 use std::ops::RangeInclusive;
 
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+use crate::trace::Level;
+use crate::trace_event;
+
 const DATA_FILE: &str = "17.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> String {
+pub fn data<P: AsRef<std::path::Path>>(
+    data_dir: P,
+) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>)> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap()).next().unwrap()
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+fn parse_range(parser: &mut LineParser, axis: &str) -> Result<(isize, isize), ParseError> {
+    parser.expect(axis)?;
+    let lower = parser.integer()?;
+    parser.expect("..")?;
+    let upper = parser.integer()?;
+    Ok((lower, upper))
 }
 
-pub fn parse(data: &str) -> (RangeInclusive<isize>, RangeInclusive<isize>) {
-    let ranges = data.strip_prefix("target area: ").unwrap();
-    let (x_range_str, y_range_str) = ranges
-        .split_once(", ")
-        .map(|(x, y)| (x.to_string(), (y.to_string())))
-        .unwrap();
-    let (x_lower, x_upper) = x_range_str
-        .strip_prefix("x=")
-        .unwrap()
-        .split_once("..")
-        .map(|(l, u)| (l.parse::<isize>().unwrap(), u.parse::<isize>().unwrap()))
-        .unwrap();
-    let (y_lower, y_upper) = y_range_str
-        .strip_prefix("y=")
-        .unwrap()
-        .split_once("..")
-        .map(|(l, u)| (l.parse::<isize>().unwrap(), u.parse::<isize>().unwrap()))
-        .unwrap();
+pub fn parse(data: &str) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>), ParseError> {
+    let mut parser = LineParser::new(1, data);
+    parser.expect("target area: ")?;
+    let (x_lower, x_upper) = parse_range(&mut parser, "x=")?;
+    parser.expect(", ")?;
+    let (y_lower, y_upper) = parse_range(&mut parser, "y=")?;
+    parser.end()?;
 
     // Handle negative ranges properly - rust ranges don't radiate from 0,
     // they're always from strictly lower to strictly greater
     let x_range = x_lower.min(x_upper)..=x_lower.max(x_upper);
     let y_range = y_lower.min(y_upper)..=y_lower.max(y_upper);
-    (x_range, y_range)
+    Ok((x_range, y_range))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>)> {
+    let line = data
+        .lines()
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(1, "a target area"))??;
+    Ok(parse(&line)?)
+}
+
+pub fn parse_str(data: &str) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>)> {
+    parse_reader(data.as_bytes())
 }
 
 fn target_distance(a: isize, a_range: &RangeInclusive<isize>) -> isize {
     if a_range.contains(&a) {
-        //println!("[within] {:3} <= {:3} <= {:3}", a_range.start(), a, a_range.end());
         0
     } else if a < *a_range.start() {
-        //println!("[short ] a: {:3} a_lower: {:3} -> {:3}", a, a_range.start(), a - a_range.start());
         a - a_range.start()
     } else if a > *a_range.end() {
-        //println!("[past  ] a: {:3} a_upper: {:3} -> {:3}", a, a_range.end(), a - a_range.end());
         a - a_range.end()
     } else {
         unreachable!();
@@ -108,29 +122,31 @@ impl War {
         let x_dist = target_distance(self.x, &self.x_range);
         let y_dist = target_distance(self.y, &self.y_range);
 
-        if x_dist == 0 && y_dist == 0 {
-            Outcome::Kaboom
+        let reason = if x_dist == 0 && y_dist == 0 {
+            return Outcome::Kaboom;
         } else if x_dist > 0 {
             // dx can never be negative, so if the shot position is higher than the target x upper limit,
             // we've overshot
-            if cfg!(debug_assertions) {
-                println!("X overshoot");
-            }
-            Outcome::Miss(x_dist, y_dist)
+            "x overshoot"
         } else if x_dist < 0 && self.dx == 0 {
             // X will make no more progress, and it's short of the target
-            if cfg!(debug_assertions) {
-                println!("X undershoot");
-            }
-            Outcome::Miss(x_dist, y_dist)
+            "x undershoot"
         } else if y_dist < 0 && self.dy < 0 {
             // dy will always eventually flip from positive to negative, so if y is lower than the lower
             // limit and dy is negative, we've overshot
-            //println!("Y overshoot");
-            Outcome::Miss(x_dist, y_dist)
+            "y overshoot"
         } else {
-            Outcome::InFlight(x_dist, y_dist)
-        }
+            return Outcome::InFlight(x_dist, y_dist);
+        };
+        trace_event!(
+            Day17::DAY,
+            Level::Trace,
+            "shot missed",
+            reason = reason,
+            x = self.x,
+            y = self.y
+        );
+        Outcome::Miss(x_dist, y_dist)
     }
 
     fn step(&mut self) -> Outcome {
@@ -156,28 +172,38 @@ fn its_war_then(
     x_range: &RangeInclusive<isize>,
     y_range: &RangeInclusive<isize>,
 ) -> (isize, usize) {
-    let mut max_y = std::isize::MIN;
+    let mut max_y = isize::MIN;
     let mut firing_solutions: usize = 0;
 
     let mut war = War::start(0, 0, x_range.clone(), y_range.clone());
     for dx in 0..=1000isize {
         for dy in -1000..=1000isize {
-            //println!("Starting war: dx: {:3} dy: {:3}", dx, dy);
             war.repeat(dx, dy);
             let mut outcome = war.step();
             while outcome.in_flight() {
-                //println!("\t{:?} => {:?}", &war, &outcome);
                 outcome = war.step();
             }
-            //println!("\t{:?} => {:?}", &war, &outcome);
-            if let Outcome::Miss(_dist_x, _dist_y) = outcome {
-                //println!("Missed it by [{}, {}] that much", dist_x, dist_y);
-            } else {
+            if outcome == Outcome::Kaboom {
+                trace_event!(
+                    Day17::DAY,
+                    Level::Debug,
+                    "shot hit",
+                    dx = dx,
+                    dy = dy,
+                    max_y = war.max_y()
+                );
                 firing_solutions += 1;
                 max_y = max_y.max(war.max_y());
             }
         }
     }
+    trace_event!(
+        Day17::DAY,
+        Level::Info,
+        "barrage finished",
+        hits = firing_solutions,
+        max_y = max_y
+    );
     (max_y, firing_solutions)
 }
 
@@ -189,6 +215,25 @@ pub fn star2(ranges: &(RangeInclusive<isize>, RangeInclusive<isize>)) -> usize {
     its_war_then(&ranges.0, &ranges.1).1
 }
 
+pub struct Day17;
+
+impl Puzzle for Day17 {
+    const DAY: u8 = 17;
+    type Input = (RangeInclusive<isize>, RangeInclusive<isize>);
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,24 +282,60 @@ mod tests {
         assert_eq!(war.step(), Outcome::InFlight(-3, 5), "war: {:?}", war);
         assert_eq!(war.step(), Outcome::InFlight(-1, 1), "war: {:?}", war);
         assert_eq!(war.step(), Outcome::Kaboom, "war: {:?}", war);
+
+        war.repeat(30, 0);
+        let (outcome, events) = crate::trace::capture(Day17::DAY, Level::Trace, || war.step());
+        assert_eq!(outcome, Outcome::Miss(10, 10));
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].to_string(),
+            "[Day 17 TRACE] shot missed reason=x overshoot x=30 y=0"
+        );
     }
 
-    const SAMPLE_DATA: [(&'static str, isize, usize); 1] =
-        [("target area: x=20..30, y=-10..-5", 45, 112)];
+    const SAMPLE_DATA: [(&str, isize, usize); 1] = [("target area: x=20..30, y=-10..-5", 45, 112)];
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("target area: x=20..30, y=-10..-5").unwrap(),
+            (20..=30, -10..=-5)
+        );
+
+        let err = parse("target area: x=20..30, y=-10").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 29));
+        assert_eq!(err.expected, "\"..\"");
+
+        let err = parse("target area: x=20..30; y=-10..-5").unwrap_err();
+        assert_eq!(err.column, 22);
+
+        assert!(parse("target: x=20..30, y=-10..-5").is_err());
+        assert!(parse("target area: x=20..3a, y=-10..-5").is_err());
+        assert!(parse("target area: x=20..30, y=-10..-5 ").is_ok());
+        assert!(parse_str("").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA[0].0;
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
 
     #[test]
     fn test_star1() {
-        for (_i, (input, output, _)) in SAMPLE_DATA.iter().enumerate() {
-            let data = parse(input);
+        for (input, output, _) in SAMPLE_DATA.iter() {
+            let data = parse_str(input).unwrap();
             assert_eq!(star1(&data), *output);
         }
     }
 
     #[test]
     fn test_star2() {
-        for (_i, (input, _, output)) in SAMPLE_DATA.iter().enumerate() {
-            let data = parse(input);
-            assert_eq!(star2(&data), *output);
+        for (input, _, output) in SAMPLE_DATA.iter() {
+            let data = parse_str(input).unwrap();
+            let (hits, events) = crate::trace::capture(Day17::DAY, Level::Debug, || star2(&data));
+            assert_eq!(hits, *output);
+            assert_eq!(events.iter().filter(|e| e.name == "shot hit").count(), hits);
         }
     }
 }
@@ -1,16 +1,38 @@
+use anyhow::Result;
 use std::io::BufRead;
 use std::ops::Add;
 
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+use crate::trace::Level;
+use crate::trace_event;
+
 const DATA_FILE: &str = "18.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> impl Iterator<Item = String> {
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Pair>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file).unwrap());
-    data.lines().map(|s_res| s_res.unwrap())
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Vec<Pair>, ParseError> {
+    let pairs = data
+        .enumerate()
+        .map(|(i, line)| Pair::parse_line(i + 1, &line))
+        .collect::<Result<Vec<Pair>, ParseError>>()?;
+    if pairs.is_empty() {
+        return Err(ParseError::end_of_input(1, "a snailfish number"));
+    }
+    Ok(pairs)
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Pair>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
 }
 
-pub fn parse<I: Iterator<Item = String>>(data: I) -> Vec<Pair> {
-    data.map(|s| Pair::from(s.as_bytes())).collect()
+pub fn parse_str(data: &str) -> Result<Vec<Pair>> {
+    parse_reader(data.as_bytes())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,45 +41,11 @@ pub enum Pair {
     Pair(Box<Pair>, Box<Pair>),
 }
 
-impl From<&[u8]> for Pair {
-    fn from(data: &[u8]) -> Self {
-        assert!(data.starts_with(b"["));
-        assert!(data.ends_with(b"]"));
-        let contents = &data[1..data.len() - 1];
-        let split_idx: usize = contents
-            .iter()
-            .enumerate()
-            .scan((0, false), |(count, fin), (idx, x)| {
-                if *fin {
-                    None
-                } else if *x == b'[' {
-                    *count += 1;
-                    Some(idx)
-                } else if *x == b']' {
-                    *count -= 1;
-                    Some(idx)
-                } else if *x == b',' && *count == 0 {
-                    *fin = true;
-                    Some(idx)
-                } else {
-                    Some(idx)
-                }
-            })
-            .last()
-            .unwrap();
-        let (left_bytes, right_bytes) = contents.split_at(split_idx);
-        let right_bytes = right_bytes.split_at(1).1;
-        let left = if left_bytes.len() == 1 {
-            Pair::Literal(left_bytes[0] - b'0')
-        } else {
-            Pair::from(left_bytes)
-        };
-        let right = if right_bytes.len() == 1 {
-            Pair::Literal(right_bytes[0] - b'0')
-        } else {
-            Pair::from(right_bytes)
-        };
-        Pair::Pair(Box::new(left), Box::new(right))
+impl std::str::FromStr for Pair {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Pair::parse_line(1, s)
     }
 }
 
@@ -97,6 +85,36 @@ impl Default for Pair {
 }
 
 impl Pair {
+    /// Parse a single snailfish number from `text`, which is line `line` of the input
+    pub fn parse_line(line: usize, text: &str) -> std::result::Result<Self, ParseError> {
+        let mut parser = LineParser::new(line, text);
+        if parser.peek() != Some('[') {
+            return Err(parser.error("\"[\""));
+        }
+        let pair = Pair::parse_element(&mut parser, 0)?;
+        parser.end()?;
+        Ok(pair)
+    }
+
+    fn parse_element(parser: &mut LineParser, depth: u8) -> std::result::Result<Self, ParseError> {
+        match parser.peek() {
+            // Anything nested any deeper would already have exploded, and
+            // reduction can't cope with it
+            Some('[') if depth >= 4 => Err(parser.error("a regular number")),
+            Some('[') => {
+                parser.expect("[")?;
+                let left = Pair::parse_element(parser, depth + 1)?;
+                parser.expect(",")?;
+                let right = Pair::parse_element(parser, depth + 1)?;
+                parser.expect("]")?;
+                Ok(Pair::Pair(Box::new(left), Box::new(right)))
+            }
+            _ => parser
+                .char_matching("\"[\" or a regular number", |c| c.is_ascii_digit())
+                .map(|c| Pair::Literal(c as u8 - b'0')),
+        }
+    }
+
     fn eval(&self) -> u64 {
         match self {
             Pair::Literal(u) => *u as u64,
@@ -118,12 +136,14 @@ impl Pair {
 
     fn add_right(&mut self, value: u8) {
         if let Pair::Literal(ref mut u) = self {
-            /*
-            println!(
-                "Explosion value {} being added to rightmost literal {}",
-                value, u
+            trace_event!(
+                Day18::DAY,
+                Level::Trace,
+                "explosion carried",
+                literal = "rightmost",
+                value = value,
+                onto = u
             );
-            */
             *u += value;
         }
         if let Pair::Pair(_, r) = self {
@@ -133,12 +153,14 @@ impl Pair {
 
     fn add_left(&mut self, value: u8) {
         if let Pair::Literal(ref mut u) = self {
-            /*
-            println!(
-                "Explosion value {} being added to leftmost literal {}",
-                value, u
+            trace_event!(
+                Day18::DAY,
+                Level::Trace,
+                "explosion carried",
+                literal = "leftmost",
+                value = value,
+                onto = u
             );
-            */
             *u += value;
         }
         if let Pair::Pair(l, _) = self {
@@ -156,23 +178,11 @@ impl Pair {
                 float.0 = l_float.0
             }
             if let Some(u) = l_float.1 {
-                /*
-                println!(
-                    "Adding {} to left-most descendent of right child {:?}",
-                    u, r
-                );
-                */
                 r.add_left(u);
             }
 
             let r_float = r.find_exploders(depth + 1, not_reduced);
             if let Some(u) = r_float.0 {
-                /*
-                println!(
-                    "Adding {} to right-most descendent of left child {:?}",
-                    u, l
-                );
-                */
                 l.add_right(u);
             }
             if r_float.1.is_some() {
@@ -188,7 +198,13 @@ impl Pair {
         if let Self::Pair(l, r) = self {
             *not_reduced = true;
             let (float_l, float_r) = (l.unwrap(), r.unwrap());
-            //println!("Exploding [{}, {}]", float_l, float_r);
+            trace_event!(
+                Day18::DAY,
+                Level::Debug,
+                "pair exploded",
+                left = float_l,
+                right = float_r
+            );
             *self = Self::default();
             (Some(float_l), Some(float_r))
         } else {
@@ -201,7 +217,6 @@ impl Pair {
             return (None, None);
         }
 
-        //println!("Exploding {:?}", self);
         match depth.cmp(&4) {
             std::cmp::Ordering::Less => self.explode_children(depth, not_reduced),
             std::cmp::Ordering::Equal => self.explode(not_reduced),
@@ -220,7 +235,14 @@ impl Pair {
             if *u >= 10 {
                 let left = *u / 2;
                 let right = (*u / 2) + (*u % 2);
-                //println!("Split: {} -> [{}, {}]", u, left, right);
+                trace_event!(
+                    Day18::DAY,
+                    Level::Debug,
+                    "number split",
+                    value = u,
+                    left = left,
+                    right = right
+                );
                 *self = Pair::Pair(
                     Box::new(Pair::Literal(left)),
                     Box::new(Pair::Literal(right)),
@@ -274,20 +296,88 @@ pub fn star2(snailpairs: &[Pair]) -> u64 {
     max_magnitude
 }
 
+pub struct Day18;
+
+impl Puzzle for Day18 {
+    const DAY: u8 = 18;
+    type Input = Vec<Pair>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::Found;
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "[[1,2],3".parse::<Pair>().unwrap_err();
+        assert_eq!((err.column, err.found), (9, Found::EndOfLine));
+
+        let err = "[[1,2];3]".parse::<Pair>().unwrap_err();
+        assert_eq!((err.column, err.found), (7, Found::Char(';')));
+
+        let err = "[[[[[1,2],3],4],5],6]".parse::<Pair>().unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (5, "a regular number"));
+
+        let err = parse_str("[1,2]\n[1,x]\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 4));
+
+        assert!("5".parse::<Pair>().is_err());
+        assert!("[1,2]]".parse::<Pair>().is_err());
+        assert!("[12,2]".parse::<Pair>().is_err());
+        assert!(parse_str("").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.0.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
 
     #[test]
     fn test_addition() {
-        let left = Pair::from("[[[[4,3],4],4],[7,[[8,4],9]]]".as_bytes());
-        let right = Pair::from("[1,1]".as_bytes());
-        let sum = Pair::from("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".as_bytes());
+        let left = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<Pair>().unwrap();
+        let right = "[1,1]".parse::<Pair>().unwrap();
+        let sum = "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".parse::<Pair>().unwrap();
 
         assert_eq!(left + right, sum);
     }
 
-    const SAMPLE_ADDITION_DATA: [&'static str; 10] = [
+    #[test]
+    fn test_trace() {
+        let left = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<Pair>().unwrap();
+        let right = "[1,1]".parse::<Pair>().unwrap();
+        let sum = "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".parse::<Pair>().unwrap();
+
+        let (result, events) = crate::trace::capture(Day18::DAY, Level::Debug, || left + right);
+        assert_eq!(result, sum);
+        let steps: Vec<String> = events.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            steps,
+            [
+                "[Day 18 DEBUG] pair exploded left=4 right=3",
+                "[Day 18 DEBUG] pair exploded left=8 right=4",
+                "[Day 18 DEBUG] number split value=15 left=7 right=8",
+                "[Day 18 DEBUG] number split value=13 left=6 right=7",
+                "[Day 18 DEBUG] pair exploded left=6 right=7",
+            ]
+        );
+    }
+
+    const SAMPLE_ADDITION_DATA: [&str; 10] = [
         "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]",
         "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]",
         "[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]",
@@ -302,16 +392,18 @@ mod tests {
 
     #[test]
     fn test_addition_sample() {
-        let data = parse(SAMPLE_ADDITION_DATA.iter().map(ToString::to_string));
+        let data = parse_str(&SAMPLE_ADDITION_DATA.join("\n")).unwrap();
         let sum: Pair = data.iter().cloned().sum();
 
         assert_eq!(
             sum,
-            Pair::from("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]".as_bytes())
+            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+                .parse::<Pair>()
+                .unwrap()
         );
     }
 
-    const SAMPLE_MAGNITUDE_DATA: [&'static str; 9] = [
+    const SAMPLE_MAGNITUDE_DATA: [&str; 9] = [
         "[9,1]",
         "[1,9]",
         "[[9,1],[1,9]]",
@@ -329,15 +421,14 @@ mod tests {
     fn test_magnitude() {
         for (pair, sol) in SAMPLE_MAGNITUDE_DATA
             .iter()
-            .map(|s| s.as_bytes())
-            .map(|b| Pair::from(b))
+            .map(|s| s.parse::<Pair>().unwrap())
             .zip(SAMPLE_MAGNITUDE_SOLUTIONS.iter())
         {
             assert_eq!(pair.eval(), *sol, "{:?} => {}", &pair, sol);
         }
     }
 
-    const SAMPLE_DATA: ([&'static str; 10], &'static str, u64) = (
+    const SAMPLE_DATA: ([&str; 10], &str, u64) = (
         [
             "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]",
             "[[[5,[2,8]],4],[5,[[9,9],0]]]",
@@ -356,9 +447,9 @@ mod tests {
 
     #[test]
     fn test_star1() {
-        let data = parse(SAMPLE_DATA.0.iter().map(ToString::to_string));
+        let data = parse_str(&SAMPLE_DATA.0.join("\n")).unwrap();
         let sum: Pair = data.iter().cloned().sum();
-        let sum_sol: Pair = Pair::from(SAMPLE_DATA.1.as_bytes());
+        let sum_sol: Pair = SAMPLE_DATA.1.parse().unwrap();
 
         assert_eq!(sum, sum_sol);
         assert_eq!(sum.eval(), SAMPLE_DATA.2);
@@ -0,0 +1,453 @@
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "19.txt";
+
+/// Two scanners are known to overlap once this many beacons line up
+const OVERLAP: usize = 12;
+
+pub type Point = [i32; 3];
+
+/// A 3-D rotation by a multiple of 90 degrees about each axis
+pub(crate) type Rotation = [[i32; 3]; 3];
+
+/// The beacons a single scanner can see, relative to itself and in its own
+/// unknown orientation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scanner {
+    pub id: usize,
+    pub beacons: Vec<Point>,
+}
+
+/// Every beacon and scanner, relative to the first scanner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconMap {
+    pub beacons: HashSet<Point>,
+    /// Scanner positions, in the order the scanners were reported
+    pub scanners: Vec<Point>,
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Scanner>> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+fn parse_header(parser: &mut LineParser) -> Result<usize, ParseError> {
+    parser.expect("--- scanner ")?;
+    let id = parser.integer()?;
+    parser.expect(" ---")?;
+    parser.end()?;
+    Ok(id)
+}
+
+fn parse_beacon(parser: &mut LineParser) -> Result<Point, ParseError> {
+    let x = parser.integer()?;
+    parser.expect(",")?;
+    let y = parser.integer()?;
+    parser.expect(",")?;
+    let z = parser.integer()?;
+    parser.end()?;
+    Ok([x, y, z])
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Vec<Scanner>, ParseError> {
+    let mut scanners: Vec<Scanner> = Vec::with_capacity(40);
+    let mut current: Option<Scanner> = None;
+    for (i, line) in data.enumerate() {
+        let mut parser = LineParser::new(i + 1, &line);
+        match current.as_mut() {
+            None => {
+                let id = parse_header(&mut parser)?;
+                current = Some(Scanner {
+                    id,
+                    beacons: Vec::with_capacity(30),
+                });
+            }
+            Some(_) if parser.is_empty() => scanners.extend(current.take()),
+            Some(scanner) => scanner.beacons.push(parse_beacon(&mut parser)?),
+        }
+    }
+    scanners.extend(current);
+    if scanners.is_empty() {
+        return Err(ParseError::end_of_input(1, "a scanner report"));
+    }
+    Ok(scanners)
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Scanner>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Vec<Scanner>> {
+    parse_reader(data.as_bytes())
+}
+
+fn determinant(m: &Rotation) -> i32 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// The 24 ways a scanner can be facing: every signed permutation of the
+/// axes, except for the mirror images
+pub(crate) fn rotations() -> Vec<Rotation> {
+    const PERMUTATIONS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut rotations = Vec::with_capacity(24);
+    for perm in PERMUTATIONS {
+        for signs in 0..8 {
+            let mut m: Rotation = [[0; 3]; 3];
+            for (row, axis) in perm.iter().enumerate() {
+                m[row][*axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+            }
+            if determinant(&m) == 1 {
+                rotations.push(m);
+            }
+        }
+    }
+    rotations
+}
+
+pub(crate) fn rotate(m: &Rotation, p: &Point) -> Point {
+    let mut rotated = [0; 3];
+    for (row, out) in rotated.iter_mut().enumerate() {
+        *out = (0..3).map(|col| m[row][col] * p[col]).sum();
+    }
+    rotated
+}
+
+fn manhattan(a: &Point, b: &Point) -> i32 {
+    (0..3).map(|i| (a[i] - b[i]).abs()).sum()
+}
+
+/// Find the orientation and position of `scanner` that lines up at least
+/// `OVERLAP` of its beacons with the already placed `fixed` beacons. Returns
+/// the scanner's beacons in the frame of `fixed`, and its position.
+fn align_pair(
+    fixed: &[Point],
+    scanner: &[Point],
+    rotations: &[Rotation],
+) -> Option<(Vec<Point>, Point)> {
+    for rotation in rotations {
+        let rotated: Vec<Point> = scanner.iter().map(|p| rotate(rotation, p)).collect();
+        let mut offsets: HashMap<Point, usize> =
+            HashMap::with_capacity(fixed.len() * scanner.len());
+        for a in fixed {
+            for b in &rotated {
+                let offset = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+                let count = offsets.entry(offset).or_insert(0);
+                *count += 1;
+                if *count >= OVERLAP {
+                    let placed = rotated
+                        .iter()
+                        .map(|p| [p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]])
+                        .collect();
+                    return Some((placed, offset));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Place every scanner relative to the first one, by repeatedly aligning an
+/// unplaced scanner against one that has just been placed
+pub fn align(scanners: &[Scanner]) -> Result<BeaconMap> {
+    let rotations = rotations();
+    let mut placed: Vec<Option<(Vec<Point>, Point)>> = vec![None; scanners.len()];
+    if let Some(first) = scanners.first() {
+        placed[0] = Some((first.beacons.clone(), [0, 0, 0]));
+    }
+
+    let mut to_visit = vec![0];
+    while let Some(i) = to_visit.pop() {
+        for j in 0..scanners.len() {
+            if placed[j].is_some() {
+                continue;
+            }
+            let fixed = &placed[i]
+                .as_ref()
+                .expect("Only placed scanners are visited")
+                .0;
+            if let Some(alignment) = align_pair(fixed, &scanners[j].beacons, &rotations) {
+                placed[j] = Some(alignment);
+                to_visit.push(j);
+            }
+        }
+    }
+
+    let mut map = BeaconMap {
+        beacons: HashSet::with_capacity(scanners.len() * 30),
+        scanners: Vec::with_capacity(scanners.len()),
+    };
+    for (scanner, alignment) in scanners.iter().zip(placed) {
+        match alignment {
+            Some((beacons, position)) => {
+                map.beacons.extend(beacons);
+                map.scanners.push(position);
+            }
+            None => bail!(
+                "Scanner {} doesn't overlap with the other scanners",
+                scanner.id
+            ),
+        }
+    }
+    Ok(map)
+}
+
+pub fn star1(scanners: &[Scanner]) -> Result<usize> {
+    Ok(align(scanners)?.beacons.len())
+}
+
+pub fn star2(scanners: &[Scanner]) -> Result<i32> {
+    let map = align(scanners)?;
+    Ok(map
+        .scanners
+        .iter()
+        .flat_map(|a| map.scanners.iter().map(move |b| manhattan(a, b)))
+        .max()
+        .unwrap_or(0))
+}
+
+pub struct Day19;
+
+impl Puzzle for Day19 {
+    const DAY: u8 = 19;
+    type Input = Vec<Scanner>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: &str = "\
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14
+";
+
+    #[test]
+    fn test_rotations() {
+        let rotations = rotations();
+        assert_eq!(rotations.len(), 24);
+        let p = [1, 2, 3];
+        let images: HashSet<Point> = rotations.iter().map(|r| rotate(r, &p)).collect();
+        assert_eq!(images.len(), 24);
+        assert!(images.contains(&[1, 2, 3]));
+        assert!(images.contains(&[-2, 1, 3]));
+        assert!(!images.contains(&[-1, 2, 3]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let scanners = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(scanners.len(), 5);
+        assert_eq!(scanners[2].id, 2);
+
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 1);
+
+        let err = parse_str("--- scanner 0 ---\n1,2\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 4));
+
+        assert!(parse_str("--- scanner x ---\n").is_err());
+        assert!(parse_str("1,2,3\n").is_err());
+        assert!(parse_str("--- scanner 0 ---\n1,2,3,4\n").is_err());
+
+        // No truncation of the sample input may panic
+        for end in 0..SAMPLE_DATA.len() {
+            let _ = parse_str(&SAMPLE_DATA[..end]);
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        let scanners = parse_str(SAMPLE_DATA).unwrap();
+        let map = align(&scanners).unwrap();
+        assert_eq!(
+            map.scanners,
+            vec![
+                [0, 0, 0],
+                [68, -1246, -43],
+                [1105, -1205, 1229],
+                [-92, -2380, -20],
+                [-20, -1133, 1061]
+            ]
+        );
+
+        // Scanners 0 and 2 only overlap by way of scanners 1 and 4
+        let ends = [scanners[0].clone(), scanners[2].clone()];
+        assert!(align(&ends).is_err());
+    }
+
+    #[test]
+    fn test_star1() {
+        let scanners = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star1(&scanners).unwrap(), 79);
+    }
+
+    #[test]
+    fn test_star2() {
+        let scanners = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&scanners).unwrap(), 3621);
+    }
+}
@@ -2,6 +2,9 @@ use anyhow::{anyhow, Error, Result};
 use std::convert::TryFrom;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "2.txt";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,7 +34,10 @@ impl std::convert::TryFrom<&str> for Travel {
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Travel>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Travel>> {
     data.lines()
         .map(|s_res| {
             s_res
@@ -41,6 +47,10 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Travel>> {
         .collect()
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<Travel>> {
+    parse_reader(data.as_bytes())
+}
+
 pub fn star1(data: &[Travel]) -> u32 {
     let (x, y) = data
         .iter()
@@ -61,12 +71,31 @@ pub fn star2(data: &[Travel]) -> u32 {
     (x * y).wrapping_abs() as u32
 }
 
+pub struct Day2;
+
+impl Puzzle for Day2 {
+    const DAY: u8 = 2;
+    type Input = Vec<Travel>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::convert::TryFrom;
 
-    const SAMPLE_DATA: [&'static str; 6] = [
+    const SAMPLE_DATA: [&str; 6] = [
         "forward 5",
         "down 5",
         "forward 8",
@@ -91,6 +120,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_str() {
+        let data = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(data, SAMPLE_DATA_CONVERTED);
+        assert!(parse_str("sideways 5").is_err());
+    }
+
     #[test]
     fn test_star1() {
         assert_eq!(star1(&SAMPLE_DATA_CONVERTED), 150);
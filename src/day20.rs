@@ -0,0 +1,317 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::io::BufRead;
+
+use crate::answer::{Answer, Bitmap};
+use crate::grid::Grid;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "20.txt";
+
+/// The image enhancement algorithm, and the image to enhance with it
+pub type TrenchMap = (Algorithm, Image);
+
+/// Whether each 3x3 neighbourhood, read as a 9-bit number with the top left
+/// pixel as the most significant bit, turns into a lit pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Algorithm {
+    table: Vec<bool>,
+}
+
+impl Algorithm {
+    pub const LEN: usize = 512;
+
+    pub fn lights(&self, neighbourhood: usize) -> bool {
+        self.table[neighbourhood]
+    }
+}
+
+/// A window onto an infinite image. Every pixel outside of the window has
+/// the same value, the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<bool>,
+    background: bool,
+}
+
+impl Image {
+    /// An image with `pixels` set on an unlit background
+    pub fn new(pixels: Grid<bool>) -> Self {
+        Image {
+            pixels,
+            background: false,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.height()
+    }
+
+    /// Whether the infinite expanse outside of the window is lit
+    pub fn background(&self) -> bool {
+        self.background
+    }
+
+    /// Whether the pixel at `(x, y)` is lit. The window's top left corner is
+    /// `(0, 0)`, but the image extends forever in every direction.
+    pub fn get(&self, x: isize, y: isize) -> bool {
+        if x < 0 || y < 0 {
+            return self.background;
+        }
+        self.pixels
+            .get(x as usize, y as usize)
+            .copied()
+            .unwrap_or(self.background)
+    }
+
+    /// Every pixel in the window, along with its `(x, y)`
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), bool)> + '_ {
+        self.pixels.iter().map(|(point, lit)| (point, *lit))
+    }
+
+    /// Number of lit pixels, unless the background is lit and there are
+    /// infinitely many
+    pub fn lit_count(&self) -> Option<usize> {
+        if self.background {
+            None
+        } else {
+            Some(self.iter().filter(|(_, lit)| *lit).count())
+        }
+    }
+
+    fn neighbourhood(&self, x: isize, y: isize) -> usize {
+        let mut index = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                index = (index << 1) | self.get(x + dx, y + dy) as usize;
+            }
+        }
+        index
+    }
+
+    /// Apply `algorithm` once. The window grows by a pixel on each side,
+    /// which is as far as the original pixels can have any influence.
+    pub fn enhance(&self, algorithm: &Algorithm) -> Image {
+        let pixels = Grid::from_fn(self.width() + 2, self.height() + 2, |x, y| {
+            algorithm.lights(self.neighbourhood(x as isize - 1, y as isize - 1))
+        });
+        // Every background pixel is surrounded by nothing but background
+        let background = algorithm.lights(if self.background {
+            Algorithm::LEN - 1
+        } else {
+            0
+        });
+        Image { pixels, background }
+    }
+
+    /// Apply `algorithm` `times` times
+    pub fn enhance_times(&self, algorithm: &Algorithm, times: usize) -> Image {
+        (0..times).fold(self.clone(), |image, _| image.enhance(algorithm))
+    }
+
+    /// The pixels in the window
+    pub fn to_bitmap(&self) -> Bitmap {
+        Bitmap::from(self.pixels.clone())
+    }
+}
+
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_bitmap())
+    }
+}
+
+fn pixel(parser: &mut LineParser) -> Result<bool, ParseError> {
+    parser
+        .char_matching("'#' or '.'", |c| c == '#' || c == '.')
+        .map(|c| c == '#')
+}
+
+fn parse_algorithm(parser: &mut LineParser) -> Result<Algorithm, ParseError> {
+    let mut table = Vec::with_capacity(Algorithm::LEN);
+    while table.len() < Algorithm::LEN {
+        table.push(pixel(parser)?);
+    }
+    parser.end()?;
+    Ok(Algorithm { table })
+}
+
+fn parse_row(parser: &mut LineParser, width: Option<usize>) -> Result<Vec<bool>, ParseError> {
+    let mut row = Vec::with_capacity(width.unwrap_or(100));
+    while width.map_or(!parser.is_empty(), |w| row.len() < w) {
+        row.push(pixel(parser)?);
+    }
+    if row.is_empty() {
+        return Err(parser.error("'#' or '.'"));
+    }
+    parser.end()?;
+    Ok(row)
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<TrenchMap, ParseError> {
+    let mut lines = data.enumerate().map(|(i, line)| (i + 1, line));
+
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(1, "an image enhancement algorithm"))?;
+    let algorithm = parse_algorithm(&mut LineParser::new(line_no, &line))?;
+
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| ParseError::end_of_input(2, "a blank line"))?;
+    LineParser::new(line_no, &line).end()?;
+
+    let mut rows: Vec<Vec<bool>> = Vec::with_capacity(100);
+    for (line_no, line) in lines {
+        let width = rows.first().map(|r| r.len());
+        rows.push(parse_row(&mut LineParser::new(line_no, &line), width)?);
+    }
+    if rows.is_empty() {
+        return Err(ParseError::end_of_input(3, "an image"));
+    }
+    let pixels = Grid::from_rows(rows).expect("Rows are all the same width");
+
+    Ok((algorithm, Image::new(pixels)))
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<TrenchMap> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<TrenchMap> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<TrenchMap> {
+    parse_reader(data.as_bytes())
+}
+
+fn lit_after(algorithm: &Algorithm, image: &Image, times: usize) -> Result<usize> {
+    image
+        .enhance_times(algorithm, times)
+        .lit_count()
+        .ok_or_else(|| anyhow!("Infinitely many pixels are lit after {} steps", times))
+}
+
+pub fn star1(algorithm: &Algorithm, image: &Image) -> Result<usize> {
+    lit_after(algorithm, image, 2)
+}
+
+pub fn star2(algorithm: &Algorithm, image: &Image) -> Result<usize> {
+    lit_after(algorithm, image, 50)
+}
+
+pub struct Day20;
+
+impl Puzzle for Day20 {
+    const DAY: u8 = 20;
+    type Input = TrenchMap;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(&input.0, &input.1)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(&input.0, &input.1)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: [&str; 7] = [
+        "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#",
+        "",
+        "#..#.",
+        "#....",
+        "##..#",
+        "..#..",
+        "..###",
+    ];
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 1);
+
+        // The algorithm is one pixel short
+        let err = parse_str(&SAMPLE_DATA[0][1..]).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (1, 512));
+
+        let mut lines = SAMPLE_DATA.to_vec();
+        lines[4] = "##.x#";
+        let err = parse_str(&lines.join("\n")).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (5, 4));
+
+        lines[4] = "##..";
+        assert!(parse_str(&lines.join("\n")).is_err());
+        assert!(parse_str(&SAMPLE_DATA[..2].join("\n")).is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_enhance() {
+        let (algorithm, image) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(image.to_string(), SAMPLE_DATA[2..].join("\n"));
+        assert_eq!(image.neighbourhood(2, 2), 34);
+        assert!(algorithm.lights(34));
+
+        let once = image.enhance(&algorithm);
+        assert_eq!((once.width(), once.height()), (7, 7));
+        assert_eq!(
+            once.to_string(),
+            ".##.##.\n#..#.#.\n##.#..#\n####..#\n.#..##.\n..##..#\n...#.#."
+        );
+        assert!(!once.get(-5, 100));
+    }
+
+    #[test]
+    fn test_flashing_background() {
+        // A dark neighbourhood lights up, and a lit one goes dark
+        let mut table = vec![false; Algorithm::LEN];
+        table[0] = true;
+        let algorithm = Algorithm { table };
+        let image = Image::new(Grid::new(1, 1, false));
+
+        let once = image.enhance(&algorithm);
+        assert!(once.background());
+        assert_eq!(once.lit_count(), None);
+        assert!(star1(&algorithm, &image).is_ok());
+
+        let twice = once.enhance(&algorithm);
+        assert!(!twice.background());
+        assert_eq!(twice.lit_count(), Some(0));
+    }
+
+    #[test]
+    fn test_star1() {
+        let (algorithm, image) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star1(&algorithm, &image).unwrap(), 35);
+    }
+
+    #[test]
+    fn test_star2() {
+        let (algorithm, image) = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star2(&algorithm, &image).unwrap(), 3351);
+    }
+}
@@ -0,0 +1,433 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "21.txt";
+
+/// The starting positions of both players, counting from 1
+pub type Start = [u32; 2];
+
+/// The largest board, winning score and die that [`Rules::new`] accepts.
+/// They keep the arithmetic within `u32`s and the count of each turn's
+/// outcomes within a `u64`, and stop the quantum engine's recursion, one
+/// level per turn, from running too deep.
+pub const MAX_BOARD_SIZE: u32 = 1000;
+pub const MAX_WINNING_SCORE: u32 = 1000;
+pub const MAX_DIE_FACES: u32 = 100;
+/// The most rolls per turn [`Rules::new`] accepts, as `MAX_DIE_FACES` to
+/// this power still fits in a `u64`
+pub const MAX_ROLLS_PER_TURN: u32 = 9;
+
+/// The parameters of a game of Dirac Dice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rules {
+    /// Number of spaces on the circular track, numbered from 1
+    board_size: u32,
+    /// A player wins as soon as their score reaches this
+    winning_score: u32,
+    /// The die rolls every number from 1 up to this
+    die_faces: u32,
+    rolls_per_turn: u32,
+}
+
+impl Rules {
+    /// The practice game, with a 100-sided deterministic die
+    pub const PRACTICE: Rules = Rules {
+        board_size: 10,
+        winning_score: 1000,
+        die_faces: 100,
+        rolls_per_turn: 3,
+    };
+
+    /// The real game, with a 3-sided Dirac die
+    pub const DIRAC: Rules = Rules {
+        board_size: 10,
+        winning_score: 21,
+        die_faces: 3,
+        rolls_per_turn: 3,
+    };
+
+    /// Rules for a game on a track with `board_size` spaces, with a die
+    /// numbered from 1 to `die_faces`, neither of which can be zero. No
+    /// parameter can be larger than its `MAX_` constant.
+    pub fn new(
+        board_size: u32,
+        winning_score: u32,
+        die_faces: u32,
+        rolls_per_turn: u32,
+    ) -> Result<Rules> {
+        if board_size == 0 {
+            bail!("The board needs at least one space");
+        }
+        if die_faces == 0 {
+            bail!("The die needs at least one face");
+        }
+        for (name, value, max) in [
+            ("board size", board_size, MAX_BOARD_SIZE),
+            ("winning score", winning_score, MAX_WINNING_SCORE),
+            ("number of die faces", die_faces, MAX_DIE_FACES),
+            (
+                "number of rolls per turn",
+                rolls_per_turn,
+                MAX_ROLLS_PER_TURN,
+            ),
+        ] {
+            if value > max {
+                bail!("The {} can be at most {}, not {}", name, max, value);
+            }
+        }
+        Ok(Rules {
+            board_size,
+            winning_score,
+            die_faces,
+            rolls_per_turn,
+        })
+    }
+
+    pub fn board_size(&self) -> u32 {
+        self.board_size
+    }
+
+    pub fn winning_score(&self) -> u32 {
+        self.winning_score
+    }
+
+    pub fn die_faces(&self) -> u32 {
+        self.die_faces
+    }
+
+    pub fn rolls_per_turn(&self) -> u32 {
+        self.rolls_per_turn
+    }
+
+    /// Move `spaces` forward from `position`, wrapping around the track.
+    /// Position 0 is the same space as the last one.
+    fn advance(&self, position: u32, spaces: u32) -> u32 {
+        ((position % self.board_size + self.board_size - 1 + spaces) % self.board_size) + 1
+    }
+
+    /// Starting positions are taken modulo the board size
+    fn normalize(&self, start: Start) -> Start {
+        [self.advance(start[0], 0), self.advance(start[1], 0)]
+    }
+}
+
+/// How a deterministic game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// Index of the winning player, 0 or 1
+    pub winner: usize,
+    pub scores: [u32; 2],
+    /// Number of times the die was rolled
+    pub rolls: u64,
+}
+
+impl Outcome {
+    pub fn loser_score(&self) -> u32 {
+        self.scores[1 - self.winner]
+    }
+}
+
+/// Play a game with a deterministic die, which rolls 1, 2, 3, ... and
+/// wraps back around to 1 after its last face
+pub fn play_deterministic(rules: &Rules, start: Start) -> Outcome {
+    let mut positions = rules.normalize(start);
+    let mut scores = [0; 2];
+    let mut rolls: u64 = 0;
+    let mut player = 0;
+    loop {
+        let mut spaces = 0;
+        for _ in 0..rules.rolls_per_turn {
+            spaces += (rolls % rules.die_faces as u64) as u32 + 1;
+            rolls += 1;
+        }
+        positions[player] = rules.advance(positions[player], spaces);
+        scores[player] += positions[player];
+        if scores[player] >= rules.winning_score {
+            return Outcome {
+                winner: player,
+                scores,
+                rolls,
+            };
+        }
+        player = 1 - player;
+    }
+}
+
+/// Counts the universes in which each player wins a game with a Dirac die,
+/// which splits the universe into one copy per face on every roll.
+///
+/// Results are memoized across calls, so a single engine can be queried
+/// about many different game states with the same rules.
+#[derive(Debug, Clone)]
+pub struct QuantumEngine {
+    rules: Rules,
+    /// How many universes each total of a turn's rolls happens in
+    turn_totals: Vec<(u32, u64)>,
+    /// Wins for (player to move, other player), keyed by (positions,
+    /// scores), both ordered with the player to move first
+    memo: HashMap<(Start, [u32; 2]), [u64; 2]>,
+}
+
+impl QuantumEngine {
+    pub fn new(rules: Rules) -> Self {
+        let mut totals: HashMap<u32, u64> = HashMap::new();
+        totals.insert(0, 1);
+        for _ in 0..rules.rolls_per_turn {
+            let mut next: HashMap<u32, u64> = HashMap::with_capacity(totals.len() * 2);
+            for (total, count) in totals {
+                for face in 1..=rules.die_faces {
+                    *next.entry(total + face).or_insert(0) += count;
+                }
+            }
+            totals = next;
+        }
+        let mut turn_totals: Vec<(u32, u64)> = totals.into_iter().collect();
+        turn_totals.sort_unstable();
+        QuantumEngine {
+            rules,
+            turn_totals,
+            memo: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The number of universes in which each player goes on to win, from
+    /// the given positions and scores with `turn` (0 or 1) about to move, or
+    /// an error if there are more than fit in a `u64`
+    pub fn wins(&mut self, positions: Start, scores: [u32; 2], turn: usize) -> Result<[u64; 2]> {
+        let positions = self.rules.normalize(positions);
+        if turn == 0 {
+            self.wins_from(positions, scores)
+        } else {
+            let [other, mover] =
+                self.wins_from([positions[1], positions[0]], [scores[1], scores[0]])?;
+            Ok([mover, other])
+        }
+    }
+
+    fn wins_from(&mut self, positions: Start, scores: [u32; 2]) -> Result<[u64; 2]> {
+        // Whoever moved last may already have won
+        if scores[1] >= self.rules.winning_score {
+            return Ok([0, 1]);
+        }
+        if let Some(wins) = self.memo.get(&(positions, scores)) {
+            return Ok(*wins);
+        }
+
+        let mut wins = [0u64, 0];
+        for i in 0..self.turn_totals.len() {
+            let (spaces, universes) = self.turn_totals[i];
+            let position = self.rules.advance(positions[0], spaces);
+            let score = scores[0].saturating_add(position);
+            // Now it's the other player's move
+            let [other, mover] = self.wins_from([positions[1], position], [scores[1], score])?;
+            for (total, won) in wins.iter_mut().zip([mover, other]) {
+                *total = won
+                    .checked_mul(universes)
+                    .and_then(|won| total.checked_add(won))
+                    .ok_or_else(|| anyhow!("Too many universes to count in a u64"))?;
+            }
+        }
+        self.memo.insert((positions, scores), wins);
+        Ok(wins)
+    }
+}
+
+fn parse_player(parser: &mut LineParser, player: u8) -> Result<u32, ParseError> {
+    parser.expect(&format!("Player {} starting position: ", player))?;
+    let position: u32 = parser.integer()?;
+    if position == 0 {
+        return Err(parser.error("a position from 1"));
+    }
+    parser.end()?;
+    Ok(position)
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Start, ParseError> {
+    let mut lines = data.enumerate().map(|(i, line)| (i + 1, line));
+    let mut start = [0; 2];
+    for (player, position) in start.iter_mut().enumerate() {
+        let (line_no, line) = lines
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(player + 1, "a starting position"))?;
+        *position = parse_player(&mut LineParser::new(line_no, &line), player as u8 + 1)?;
+    }
+    Ok(start)
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Start> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Start> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Start> {
+    parse_reader(data.as_bytes())
+}
+
+pub fn star1(start: &Start) -> u64 {
+    let outcome = play_deterministic(&Rules::PRACTICE, *start);
+    outcome.loser_score() as u64 * outcome.rolls
+}
+
+pub fn star2(start: &Start) -> Result<u64> {
+    let wins = QuantumEngine::new(Rules::DIRAC).wins(*start, [0, 0], 0)?;
+    Ok(wins[0].max(wins[1]))
+}
+
+pub struct Day21;
+
+impl Puzzle for Day21 {
+    const DAY: u8 = 21;
+    type Input = Start;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: [&str; 2] = [
+        "Player 1 starting position: 4",
+        "Player 2 starting position: 8",
+    ];
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_str(&SAMPLE_DATA.join("\n")).unwrap(), [4, 8]);
+
+        let err = parse_str(SAMPLE_DATA[0]).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 2);
+
+        let err =
+            parse_str("Player 1 starting position: 4\nPlayer 1 starting position: 8").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 8));
+
+        assert!(parse_str("Player 1 starting position: 0\nPlayer 2 starting position: 8").is_err());
+        assert!(parse_str("Player 1 starting position: x\nPlayer 2 starting position: 8").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let outcome = play_deterministic(&Rules::PRACTICE, [4, 8]);
+        assert_eq!(
+            outcome,
+            Outcome {
+                winner: 0,
+                scores: [1000, 745],
+                rolls: 993,
+            }
+        );
+
+        // First to 10 points: player 1 rolls 1+2+3 to move to 10, and wins
+        // before player 2 gets a turn
+        let rules = Rules::new(10, 10, 100, 3).unwrap();
+        assert_eq!(play_deterministic(&rules, [4, 8]).scores, [10, 0]);
+        // Off the end of a 6-sided die: rolls 1+2+3, 4+5+6, then 1+2+3
+        let rules = Rules::new(10, 11, 6, 3).unwrap();
+        let outcome = play_deterministic(&rules, [4, 8]);
+        assert_eq!((outcome.scores, outcome.rolls), ([16, 3], 9));
+    }
+
+    #[test]
+    fn test_quantum() {
+        let mut engine = QuantumEngine::new(Rules::DIRAC);
+        assert_eq!(
+            engine.wins([4, 8], [0, 0], 0).unwrap(),
+            [444356092776315, 341960390180808]
+        );
+
+        // A single roll of a coin decides a race to 1 point
+        let mut coin = QuantumEngine::new(Rules::new(10, 1, 2, 1).unwrap());
+        assert_eq!(coin.wins([1, 1], [0, 0], 0).unwrap(), [2, 0]);
+        assert_eq!(coin.wins([1, 1], [0, 0], 1).unwrap(), [0, 2]);
+        // Player 2 has already won
+        assert_eq!(coin.wins([1, 1], [0, 1], 0).unwrap(), [0, 1]);
+
+        // A longer game splits into more universes than can be counted
+        let mut longer = QuantumEngine::new(Rules::new(10, 40, 3, 3).unwrap());
+        assert!(longer.wins([4, 8], [0, 0], 0).is_err());
+        // As does a bigger die
+        let mut bigger = QuantumEngine::new(Rules::new(10, 21, 100, 9).unwrap());
+        assert!(bigger.wins([4, 8], [0, 0], 0).is_err());
+    }
+
+    #[test]
+    fn test_rules() {
+        assert!(Rules::new(0, 21, 3, 3).is_err());
+        assert!(Rules::new(10, 21, 0, 3).is_err());
+        assert!(Rules::new(MAX_BOARD_SIZE + 1, 21, 3, 3).is_err());
+        assert!(Rules::new(10, MAX_WINNING_SCORE + 1, 3, 3).is_err());
+        assert!(Rules::new(10, 21, MAX_DIE_FACES + 1, 3).is_err());
+        assert!(Rules::new(10, 21, 3, MAX_ROLLS_PER_TURN + 1).is_err());
+        let largest = Rules::new(
+            MAX_BOARD_SIZE,
+            MAX_WINNING_SCORE,
+            MAX_DIE_FACES,
+            MAX_ROLLS_PER_TURN,
+        )
+        .unwrap();
+        assert_eq!(
+            QuantumEngine::new(largest)
+                .turn_totals
+                .iter()
+                .map(|(_, n)| n)
+                .sum::<u64>(),
+            100u64.pow(9)
+        );
+        assert_eq!(Rules::new(10, 1000, 100, 3).unwrap(), Rules::PRACTICE);
+        let rules = Rules::new(1, 0, 1, 0).unwrap();
+        assert_eq!(
+            (rules.board_size(), rules.winning_score(), rules.die_faces()),
+            (1, 0, 1)
+        );
+        assert_eq!(play_deterministic(&rules, [1, 1]).rolls, 0);
+
+        // Position 0 is the last space, rather than off the track
+        assert_eq!(
+            play_deterministic(&Rules::PRACTICE, [0, 8]),
+            play_deterministic(&Rules::PRACTICE, [10, 8])
+        );
+    }
+
+    #[test]
+    fn test_star1() {
+        assert_eq!(star1(&[4, 8]), 739785);
+    }
+
+    #[test]
+    fn test_star2() {
+        assert_eq!(star2(&[4, 8]).unwrap(), 444356092776315);
+    }
+}
@@ -0,0 +1,392 @@
+use anyhow::Result;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "22.txt";
+
+/// How far from the origin a cuboid can reach on each axis, which keeps
+/// its volume within a `u64`
+pub const MAX_COORDINATE: i64 = 1_000_000;
+
+/// An axis-aligned box of cubes, from `min` to `max` inclusive on each axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid {
+    min: [i64; 3],
+    max: [i64; 3],
+}
+
+impl Cuboid {
+    /// The cuboid spanning `min` to `max`, unless it would be empty or reach
+    /// further than [`MAX_COORDINATE`] from the origin
+    pub fn new(min: [i64; 3], max: [i64; 3]) -> Option<Self> {
+        let in_bounds = |a: i64| a.abs() <= MAX_COORDINATE;
+        if (0..3).all(|i| min[i] <= max[i] && in_bounds(min[i]) && in_bounds(max[i])) {
+            Some(Cuboid { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// The lowest corner
+    pub fn min(&self) -> [i64; 3] {
+        self.min
+    }
+
+    /// The highest corner
+    pub fn max(&self) -> [i64; 3] {
+        self.max
+    }
+
+    /// Number of cubes inside
+    pub fn volume(&self) -> u64 {
+        (0..3)
+            .map(|i| (self.max[i] - self.min[i] + 1) as u64)
+            .product()
+    }
+
+    pub fn contains(&self, point: [i64; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// The cubes in both `self` and `other`
+    pub fn intersect(&self, other: &Cuboid) -> Option<Cuboid> {
+        let mut min = [0; 3];
+        let mut max = [0; 3];
+        for i in 0..3 {
+            min[i] = self.min[i].max(other.min[i]);
+            max[i] = self.max[i].min(other.max[i]);
+        }
+        Cuboid::new(min, max)
+    }
+
+    /// The cubes in `self` but not in `other`, as at most six disjoint
+    /// cuboids
+    pub fn subtract(&self, other: &Cuboid) -> Vec<Cuboid> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+
+        // Slice off the parts below and above the overlap one axis at a
+        // time, narrowing what's left down to the overlap itself
+        let mut pieces = Vec::with_capacity(6);
+        let mut rest = *self;
+        for axis in 0..3 {
+            if rest.min[axis] < overlap.min[axis] {
+                let mut below = rest;
+                below.max[axis] = overlap.min[axis] - 1;
+                pieces.push(below);
+            }
+            if overlap.max[axis] < rest.max[axis] {
+                let mut above = rest;
+                above.min[axis] = overlap.max[axis] + 1;
+                pieces.push(above);
+            }
+            rest.min[axis] = overlap.min[axis];
+            rest.max[axis] = overlap.max[axis];
+        }
+        pieces
+    }
+}
+
+/// A set of cubes, held as disjoint cuboids
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CuboidSet {
+    cuboids: Vec<Cuboid>,
+}
+
+impl CuboidSet {
+    pub fn new() -> Self {
+        CuboidSet::default()
+    }
+
+    pub fn cuboids(&self) -> &[Cuboid] {
+        &self.cuboids
+    }
+
+    pub fn insert(&mut self, cuboid: Cuboid) {
+        self.remove(&cuboid);
+        self.cuboids.push(cuboid);
+    }
+
+    pub fn remove(&mut self, cuboid: &Cuboid) {
+        self.cuboids = self
+            .cuboids
+            .iter()
+            .flat_map(|c| c.subtract(cuboid))
+            .collect();
+    }
+
+    pub fn contains(&self, point: [i64; 3]) -> bool {
+        self.cuboids.iter().any(|c| c.contains(point))
+    }
+
+    /// Number of cubes in the set
+    pub fn volume(&self) -> u64 {
+        self.cuboids.iter().map(Cuboid::volume).sum()
+    }
+}
+
+/// A single reboot step, turning every cube in a cuboid on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub on: bool,
+    pub cuboid: Cuboid,
+}
+
+fn parse_coordinate(parser: &mut LineParser) -> Result<i64, ParseError> {
+    let start = parser.clone();
+    let coordinate: i64 = parser.integer()?;
+    if coordinate.abs() > MAX_COORDINATE {
+        return Err(start.error(format!(
+            "a coordinate from -{} to {}",
+            MAX_COORDINATE, MAX_COORDINATE
+        )));
+    }
+    Ok(coordinate)
+}
+
+fn parse_range(parser: &mut LineParser, axis: &str) -> Result<(i64, i64), ParseError> {
+    parser.expect(axis)?;
+    let start = parser.clone();
+    let lower = parse_coordinate(parser)?;
+    parser.expect("..")?;
+    let upper = parse_coordinate(parser)?;
+    if lower > upper {
+        return Err(start.error("a range from its lower to its upper end"));
+    }
+    Ok((lower, upper))
+}
+
+fn parse_step(parser: &mut LineParser) -> Result<Step, ParseError> {
+    let on = if parser.accept("on ") {
+        true
+    } else if parser.accept("off ") {
+        false
+    } else {
+        return Err(parser.error("\"on \" or \"off \""));
+    };
+    let (x_min, x_max) = parse_range(parser, "x=")?;
+    parser.expect(",")?;
+    let (y_min, y_max) = parse_range(parser, "y=")?;
+    parser.expect(",")?;
+    let (z_min, z_max) = parse_range(parser, "z=")?;
+    parser.end()?;
+    let cuboid = Cuboid::new([x_min, y_min, z_min], [x_max, y_max, z_max])
+        .expect("Ranges are checked to be ordered and in bounds");
+    Ok(Step { on, cuboid })
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Vec<Step>, ParseError> {
+    let steps = data
+        .enumerate()
+        .map(|(i, line)| parse_step(&mut LineParser::new(i + 1, &line)))
+        .collect::<Result<Vec<Step>, ParseError>>()?;
+    if steps.is_empty() {
+        return Err(ParseError::end_of_input(1, "a reboot step"));
+    }
+    Ok(steps)
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Step>> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Step>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Vec<Step>> {
+    parse_reader(data.as_bytes())
+}
+
+/// Count the cubes left on after every step, by keeping track of
+/// overlapping cuboids with alternating signs: every cuboid turned on adds
+/// its volume, and every overlap with an earlier cuboid cancels out that
+/// cuboid's contribution to it.
+fn signed_volume(steps: &[Step]) -> u64 {
+    let mut signed: Vec<(Cuboid, i64)> = Vec::with_capacity(steps.len() * 4);
+    for step in steps {
+        let overlaps: Vec<(Cuboid, i64)> = signed
+            .iter()
+            .filter_map(|(cuboid, sign)| cuboid.intersect(&step.cuboid).map(|o| (o, -sign)))
+            .collect();
+        signed.extend(overlaps);
+        if step.on {
+            signed.push((step.cuboid, 1));
+        }
+    }
+    // Overlapping cuboids can add up to more than fits in an `i64` before
+    // they cancel out
+    let total: i128 = signed
+        .iter()
+        .map(|(cuboid, sign)| *sign as i128 * cuboid.volume() as i128)
+        .sum();
+    total as u64
+}
+
+pub fn star1(steps: &[Step]) -> u64 {
+    let region = Cuboid::new([-50; 3], [50; 3]).expect("The region isn't empty");
+    let mut reactor = CuboidSet::new();
+    for step in steps {
+        if let Some(cuboid) = step.cuboid.intersect(&region) {
+            if step.on {
+                reactor.insert(cuboid);
+            } else {
+                reactor.remove(&cuboid);
+            }
+        }
+    }
+    reactor.volume()
+}
+
+pub fn star2(steps: &[Step]) -> u64 {
+    signed_volume(steps)
+}
+
+pub struct Day22;
+
+impl Puzzle for Day22 {
+    const DAY: u8 = 22;
+    type Input = Vec<Step>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Found;
+
+    const SAMPLE_DATA: [&str; 4] = [
+        "on x=10..12,y=10..12,z=10..12",
+        "on x=11..13,y=11..13,z=11..13",
+        "off x=9..11,y=9..11,z=9..11",
+        "on x=10..10,y=10..10,z=10..10",
+    ];
+
+    /// Reaches far outside of the initialization region
+    const LARGE_DATA: [&str; 3] = [
+        "on x=-100000..100000,y=-100000..100000,z=-100000..100000",
+        "off x=0..100000,y=0..100000,z=-100000..100000",
+        "on x=10..20,y=-5..5,z=90000..90009",
+    ];
+
+    fn cuboid(min: [i64; 3], max: [i64; 3]) -> Cuboid {
+        Cuboid::new(min, max).unwrap()
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let a = cuboid([0, 0, 0], [9, 9, 9]);
+        let b = cuboid([5, -5, 2], [20, 5, 3]);
+        assert_eq!(a.volume(), 1000);
+        assert!(Cuboid::new([0, 0, 0], [-1, 0, 0]).is_none());
+        assert!(Cuboid::new([0, 0, -MAX_COORDINATE - 1], [0, 0, 0]).is_none());
+        assert!(Cuboid::new([0, 0, 0], [0, MAX_COORDINATE + 1, 0]).is_none());
+        let largest = cuboid([-MAX_COORDINATE; 3], [MAX_COORDINATE; 3]);
+        assert_eq!(largest.volume(), (2 * MAX_COORDINATE as u64 + 1).pow(3));
+        assert_eq!((b.min(), b.max()), ([5, -5, 2], [20, 5, 3]));
+        assert_eq!(a.intersect(&b), Some(cuboid([5, 0, 2], [9, 5, 3])));
+        assert_eq!(a.intersect(&cuboid([10, 0, 0], [11, 1, 1])), None);
+
+        let pieces = a.subtract(&b);
+        assert!(pieces.len() <= 6);
+        assert_eq!(pieces.iter().map(Cuboid::volume).sum::<u64>(), 1000 - 60);
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(p.intersect(&b).is_none());
+            assert!(p.intersect(&a) == Some(*p));
+            for q in &pieces[i + 1..] {
+                assert!(p.intersect(q).is_none());
+            }
+        }
+        assert!(a.subtract(&a).is_empty());
+        assert_eq!(b.subtract(&cuboid([100; 3], [101; 3])), vec![b]);
+    }
+
+    #[test]
+    fn test_cuboid_set() {
+        let mut set = CuboidSet::new();
+        set.insert(cuboid([0, 0, 0], [2, 2, 2]));
+        set.insert(cuboid([1, 1, 1], [3, 3, 3]));
+        assert_eq!(set.volume(), 27 + 27 - 8);
+        set.remove(&cuboid([1, 1, 1], [1, 1, 1]));
+        assert_eq!(set.volume(), 45);
+        assert!(!set.contains([1, 1, 1]));
+        assert!(set.contains([3, 3, 3]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let steps = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(steps.len(), 4);
+        assert!(!steps[2].on);
+        assert_eq!(steps[2].cuboid, cuboid([9, 9, 9], [11, 11, 11]));
+
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 1);
+
+        let err = parse_str("on x=1..2,y=1..2,z=1..2\nof x=1..2,y=1..2,z=1..2\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        assert!(parse_str("on x=1..2,y=1..2\n").is_err());
+        assert!(parse_str("on x=1..2,z=1..2,y=1..2\n").is_err());
+        assert!(parse_str("on x=1..2,y=1..2,z=1..\n").is_err());
+
+        // Ranges have to go from low to high
+        let err = parse_str("on x=1..2,y=10..5,z=1..2\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.column, &err.found), (13, &Found::Char('1')));
+
+        // Nothing can reach so far that its volume would overflow
+        let far = "on x=-1000000..1000000,y=-1000000..1000000,z=-1000000..1000000";
+        let steps = parse_str(&[far, far].join("\n")).unwrap();
+        assert_eq!(steps[0].cuboid.volume(), 2_000_001u64.pow(3));
+        assert_eq!(star2(&steps), 2_000_001u64.pow(3));
+        let err = parse_str("on x=1..2,y=1..2,z=-1000001..2\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.column, &err.found), (20, &Found::Char('-')));
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_star1() {
+        let steps = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star1(&steps), 39);
+        let steps = parse_str(&LARGE_DATA.join("\n")).unwrap();
+        assert_eq!(star1(&steps), 101 * 101 * 101 - 51 * 51 * 101);
+    }
+
+    #[test]
+    fn test_star2() {
+        let steps = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star2(&steps), 39);
+        let steps = parse_str(&LARGE_DATA.join("\n")).unwrap();
+        assert_eq!(
+            star2(&steps),
+            200001 * 200001 * 200001 - 100001 * 100001 * 200001 + 11 * 6 * 10
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use anyhow::{anyhow, bail, Result};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{Found, LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "23.txt";
+
+const HALLWAY_LEN: usize = 11;
+const ROOMS: usize = 4;
+pub const MAX_DEPTH: usize = 4;
+/// The hallway space right outside of each room, where nobody may stop
+const ENTRANCES: [usize; ROOMS] = [2, 4, 6, 8];
+/// Energy for a single step, by amphipod type
+const ENERGY: [u32; ROOMS] = [1, 10, 100, 1000];
+/// The rows folded out of the diagram, inserted above the last row
+const UNFOLDED: [[u8; ROOMS]; 2] = [[4, 3, 2, 1], [4, 2, 1, 3]];
+const EMPTY: u8 = 0;
+
+fn amphipod_name(amphipod: u8) -> char {
+    (b'A' + amphipod - 1) as char
+}
+
+/// Where an amphipod is in the burrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// A space in the hallway, counting from the left
+    Hallway(usize),
+    /// A room, counting from the left, and a space in it, counting down
+    /// from the hallway
+    Room(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: Location,
+    pub to: Location,
+    pub energy: u32,
+}
+
+/// A compact snapshot of every amphipod's position. Each space holds 0 if
+/// it's empty, or 1 to 4 for an amphipod of type A to D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Burrow {
+    hallway: [u8; HALLWAY_LEN],
+    rooms: [[u8; MAX_DEPTH]; ROOMS],
+    depth: usize,
+}
+
+/// The cheapest way to organize the amphipods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub energy: u32,
+    pub moves: Vec<Move>,
+}
+
+impl Burrow {
+    /// Number of spaces in each room
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn get(&self, location: Location) -> u8 {
+        match location {
+            Location::Hallway(h) => self.hallway[h],
+            Location::Room(r, s) => self.rooms[r][s],
+        }
+    }
+
+    fn set(&mut self, location: Location, amphipod: u8) {
+        match location {
+            Location::Hallway(h) => self.hallway[h] = amphipod,
+            Location::Room(r, s) => self.rooms[r][s] = amphipod,
+        }
+    }
+
+    /// The amphipod at `location`, by name, if there is one
+    pub fn amphipod(&self, location: Location) -> Option<char> {
+        match self.get(location) {
+            EMPTY => None,
+            amphipod => Some(amphipod_name(amphipod)),
+        }
+    }
+
+    /// Whether every amphipod is in its own room
+    pub fn is_organized(&self) -> bool {
+        self.hallway.iter().all(|&a| a == EMPTY)
+            && (0..ROOMS).all(|r| {
+                self.rooms[r][..self.depth]
+                    .iter()
+                    .all(|&a| a as usize == r + 1)
+            })
+    }
+
+    /// Carry out `mv`, which is assumed to be legal
+    pub fn apply(&self, mv: &Move) -> Burrow {
+        let mut next = *self;
+        let amphipod = next.get(mv.from);
+        next.set(mv.from, EMPTY);
+        next.set(mv.to, amphipod);
+        next
+    }
+
+    /// The burrow with the two folded-away rows of the diagram restored,
+    /// unless there's no room left for them
+    pub fn unfold(&self) -> Result<Burrow> {
+        let mut unfolded = *self;
+        unfolded.depth = self.depth + UNFOLDED.len();
+        if unfolded.depth > MAX_DEPTH {
+            bail!(
+                "A burrow {} spaces deep can't be unfolded any further",
+                self.depth
+            );
+        }
+        for (r, room) in unfolded.rooms.iter_mut().enumerate() {
+            room[self.depth - 1] = UNFOLDED[0][r];
+            room[self.depth] = UNFOLDED[1][r];
+            room[self.depth + 1] = self.rooms[r][self.depth - 1];
+        }
+        Ok(unfolded)
+    }
+
+    /// Whether the hallway is clear between `from` and `to`, not counting
+    /// `from` itself
+    fn hallway_clear(&self, from: usize, to: usize) -> bool {
+        let (lo, hi) = if from < to {
+            (from + 1, to)
+        } else {
+            (to, from - 1)
+        };
+        self.hallway[lo..=hi].iter().all(|&a| a == EMPTY)
+    }
+
+    /// Whether room `r` holds nobody who needs to leave it
+    fn room_settled(&self, r: usize) -> bool {
+        self.rooms[r][..self.depth]
+            .iter()
+            .all(|&a| a == EMPTY || a as usize == r + 1)
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(28);
+
+        // Into a room, which is always the best thing to do when possible
+        for h in 0..HALLWAY_LEN {
+            let amphipod = self.hallway[h];
+            if amphipod == EMPTY {
+                continue;
+            }
+            let r = amphipod as usize - 1;
+            if !self.room_settled(r) || !self.hallway_clear(h, ENTRANCES[r]) {
+                continue;
+            }
+            let slot = match self.rooms[r][..self.depth]
+                .iter()
+                .rposition(|&a| a == EMPTY)
+            {
+                Some(slot) => slot,
+                None => continue,
+            };
+            let steps = (h as isize - ENTRANCES[r] as isize).unsigned_abs() + slot + 1;
+            return vec![Move {
+                from: Location::Hallway(h),
+                to: Location::Room(r, slot),
+                energy: steps as u32 * ENERGY[r],
+            }];
+        }
+
+        // Out into the hallway
+        for r in 0..ROOMS {
+            if self.room_settled(r) {
+                continue;
+            }
+            let slot = match self.rooms[r][..self.depth].iter().position(|&a| a != EMPTY) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            let energy = ENERGY[self.rooms[r][slot] as usize - 1];
+            for h in 0..HALLWAY_LEN {
+                if ENTRANCES.contains(&h) || !self.hallway_clear(ENTRANCES[r], h) {
+                    continue;
+                }
+                if self.hallway[h] != EMPTY {
+                    continue;
+                }
+                let steps = (h as isize - ENTRANCES[r] as isize).unsigned_abs() + slot + 1;
+                moves.push(Move {
+                    from: Location::Room(r, slot),
+                    to: Location::Hallway(h),
+                    energy: steps as u32 * energy,
+                });
+            }
+        }
+        moves
+    }
+
+    /// Find the least energy needed to organize the amphipods, and the
+    /// moves that take, with Dijkstra's algorithm
+    pub fn solve(&self) -> Option<Solution> {
+        let mut best: HashMap<Burrow, (u32, Option<(Burrow, Move)>)> = HashMap::new();
+        let mut heap: BinaryHeap<Reverse<(u32, Burrow)>> = BinaryHeap::new();
+        best.insert(*self, (0, None));
+        heap.push(Reverse((0, *self)));
+
+        while let Some(Reverse((energy, burrow))) = heap.pop() {
+            if energy > best[&burrow].0 {
+                continue;
+            }
+            if burrow.is_organized() {
+                let mut moves = Vec::new();
+                let mut current = burrow;
+                while let Some((previous, mv)) = best[&current].1 {
+                    moves.push(mv);
+                    current = previous;
+                }
+                moves.reverse();
+                return Some(Solution { energy, moves });
+            }
+            for mv in burrow.moves() {
+                let next = burrow.apply(&mv);
+                let next_energy = energy + mv.energy;
+                if !matches!(best.get(&next), Some((e, _)) if *e <= next_energy) {
+                    best.insert(next, (next_energy, Some((burrow, mv))));
+                    heap.push(Reverse((next_energy, next)));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for Burrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |a: u8| if a == EMPTY { '.' } else { amphipod_name(a) };
+        writeln!(f, "#############")?;
+        write!(f, "#")?;
+        for &a in &self.hallway {
+            write!(f, "{}", name(a))?;
+        }
+        writeln!(f, "#")?;
+        for slot in 0..self.depth {
+            write!(f, "{}", if slot == 0 { "###" } else { "  #" })?;
+            for room in &self.rooms {
+                write!(f, "{}#", name(room[slot]))?;
+            }
+            writeln!(f, "{}", if slot == 0 { "##" } else { "" })?;
+        }
+        write!(f, "  #########")
+    }
+}
+
+fn space(parser: &mut LineParser, empty_allowed: bool) -> Result<u8, ParseError> {
+    let expected = if empty_allowed {
+        "'.' or an amphipod"
+    } else {
+        "an amphipod"
+    };
+    let c = parser.char_matching(expected, |c| {
+        matches!(c, 'A'..='D') || (empty_allowed && c == '.')
+    })?;
+    Ok(if c == '.' { EMPTY } else { c as u8 - b'A' + 1 })
+}
+
+fn parse_room_row(parser: &mut LineParser, first: bool) -> Result<[u8; ROOMS], ParseError> {
+    parser.expect(if first { "###" } else { "  #" })?;
+    let mut row = [EMPTY; ROOMS];
+    for space_in_room in row.iter_mut() {
+        *space_in_room = space(parser, true)?;
+        parser.expect("#")?;
+    }
+    if first {
+        parser.expect("##")?;
+    }
+    parser.end()?;
+    Ok(row)
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Burrow, ParseError> {
+    let lines: Vec<String> = data.collect();
+    let line = |i: usize, expected: &str| {
+        lines
+            .get(i)
+            .map(|text| LineParser::new(i + 1, text))
+            .ok_or_else(|| ParseError::end_of_input(i + 1, expected))
+    };
+
+    let mut parser = line(0, "the burrow's top wall")?;
+    parser.expect("#############")?;
+    parser.end()?;
+
+    let mut parser = line(1, "the hallway")?;
+    parser.expect("#")?;
+    let mut hallway = [EMPTY; HALLWAY_LEN];
+    for space_in_hallway in hallway.iter_mut() {
+        *space_in_hallway = space(&mut parser, true)?;
+    }
+    parser.expect("#")?;
+    parser.end()?;
+
+    let mut rooms = [[EMPTY; MAX_DEPTH]; ROOMS];
+    let mut depth = 0;
+    loop {
+        let mut parser = line(depth + 2, "a row of rooms")?;
+        if depth > 0 && parser.accept("  #########") {
+            parser.end()?;
+            break;
+        }
+        if depth == MAX_DEPTH {
+            return Err(parser.error("\"  #########\""));
+        }
+        let row = parse_room_row(&mut parser, depth == 0)?;
+        for (r, (room, amphipod)) in rooms.iter_mut().zip(row).enumerate() {
+            // Amphipods can't float above an empty space
+            if depth > 0 && amphipod == EMPTY && room[depth - 1] != EMPTY {
+                return Err(ParseError {
+                    line: depth + 3,
+                    column: 4 + 2 * r,
+                    expected: "an amphipod below another".to_string(),
+                    found: Found::Char('.'),
+                });
+            }
+            room[depth] = amphipod;
+        }
+        depth += 1;
+    }
+
+    Ok(Burrow {
+        hallway,
+        rooms,
+        depth,
+    })
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Burrow> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Burrow> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Burrow> {
+    parse_reader(data.as_bytes())
+}
+
+fn least_energy(burrow: &Burrow) -> Result<u32> {
+    burrow
+        .solve()
+        .map(|solution| solution.energy)
+        .ok_or_else(|| anyhow!("There is no way to organize the amphipods"))
+}
+
+pub fn star1(burrow: &Burrow) -> Result<u32> {
+    least_energy(burrow)
+}
+
+/// Organize the unfolded burrow, or the burrow as it is if its diagram
+/// was already unfolded
+pub fn star2(burrow: &Burrow) -> Result<u32> {
+    if burrow.depth() == MAX_DEPTH {
+        least_energy(burrow)
+    } else {
+        least_energy(&burrow.unfold()?)
+    }
+}
+
+pub struct Day23;
+
+impl Puzzle for Day23 {
+    const DAY: u8 = 23;
+    type Input = Burrow;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: [&str; 5] = [
+        "#############",
+        "#...........#",
+        "###B#C#B#D###",
+        "  #A#D#C#A#",
+        "  #########",
+    ];
+
+    const UNFOLDED_DATA: [&str; 7] = [
+        "#############",
+        "#...........#",
+        "###B#C#B#D###",
+        "  #D#C#B#A#",
+        "  #D#B#A#C#",
+        "  #A#D#C#A#",
+        "  #########",
+    ];
+
+    #[test]
+    fn test_parse_errors() {
+        let burrow = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(burrow.depth(), 2);
+        assert_eq!(burrow.amphipod(Location::Room(1, 1)), Some('D'));
+        assert_eq!(burrow.amphipod(Location::Hallway(0)), None);
+        assert_eq!(burrow.to_string(), SAMPLE_DATA.join("\n"));
+
+        let err = parse_str("").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 1);
+
+        let err = parse_str(&SAMPLE_DATA[..3].join("\n")).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!(err.line, 4);
+
+        let mut lines = SAMPLE_DATA.to_vec();
+        lines[3] = "  #A#E#C#A#";
+        let err = parse_str(&lines.join("\n")).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (4, 6));
+
+        // Nobody can stand on an empty space in a room
+        let mut lines = SAMPLE_DATA.to_vec();
+        lines[2] = "###B#C#B#.###";
+        lines[3] = "  #A#D#C#.#";
+        assert!(parse_str(&lines.join("\n")).is_ok());
+        lines[3] = "  #A#D#.#A#";
+        let err = parse_str(&lines.join("\n")).unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (4, 8));
+
+        assert!(parse_str(&SAMPLE_DATA[..2].join("\n")).is_err());
+        let mut lines = SAMPLE_DATA.to_vec();
+        lines[1] = "#..........#";
+        assert!(parse_str(&lines.join("\n")).is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_unfold() {
+        let burrow = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        let unfolded = parse_str(&UNFOLDED_DATA.join("\n")).unwrap();
+        assert_eq!(burrow.unfold().unwrap(), unfolded);
+        assert_eq!(unfolded.to_string(), UNFOLDED_DATA.join("\n"));
+        assert!(unfolded.unfold().is_err());
+
+        let mut lines = UNFOLDED_DATA.to_vec();
+        lines.remove(4);
+        let three_deep = parse_str(&lines.join("\n")).unwrap();
+        assert!(three_deep.unfold().is_err());
+        assert!(star2(&three_deep).is_err());
+    }
+
+    #[test]
+    fn test_replay() {
+        for data in [&SAMPLE_DATA[..], &UNFOLDED_DATA[..]] {
+            let start = parse_str(&data.join("\n")).unwrap();
+            let solution = start.solve().unwrap();
+            let mut burrow = start;
+            for mv in &solution.moves {
+                assert!(burrow.moves().contains(mv), "{:?} in\n{}", mv, burrow);
+                burrow = burrow.apply(mv);
+            }
+            assert!(burrow.is_organized());
+            let total: u32 = solution.moves.iter().map(|mv| mv.energy).sum();
+            assert_eq!(total, solution.energy);
+        }
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let mut lines = SAMPLE_DATA.to_vec();
+        lines[3] = "  #A#D#C#B#";
+        let burrow = parse_str(&lines.join("\n")).unwrap();
+        assert!(burrow.solve().is_none());
+        assert!(star1(&burrow).is_err());
+    }
+
+    #[test]
+    fn test_star1() {
+        let burrow = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star1(&burrow).unwrap(), 12521);
+    }
+
+    #[test]
+    fn test_star2() {
+        let burrow = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star2(&burrow).unwrap(), 44169);
+        let unfolded = parse_str(&UNFOLDED_DATA.join("\n")).unwrap();
+        assert_eq!(star2(&unfolded).unwrap(), 44169);
+    }
+}
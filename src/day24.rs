@@ -0,0 +1,582 @@
+use anyhow::{anyhow, bail, Result};
+use std::fmt;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "24.txt";
+
+/// Number of digits in a model number
+const MODEL_DIGITS: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Register {
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Register::W => 'w',
+            Register::X => 'x',
+            Register::Y => 'y',
+            Register::Z => 'z',
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Register(Register),
+    Literal(i64),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(r) => write!(f, "{}", r),
+            Operand::Literal(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Inp(Register),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Div(Register, Operand),
+    Mod(Register, Operand),
+    Eql(Register, Operand),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Inp(a) => write!(f, "inp {}", a),
+            Instruction::Add(a, b) => write!(f, "add {} {}", a, b),
+            Instruction::Mul(a, b) => write!(f, "mul {} {}", a, b),
+            Instruction::Div(a, b) => write!(f, "div {} {}", a, b),
+            Instruction::Mod(a, b) => write!(f, "mod {} {}", a, b),
+            Instruction::Eql(a, b) => write!(f, "eql {} {}", a, b),
+        }
+    }
+}
+
+/// Why an ALU program stopped early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluError {
+    /// An `inp` instruction ran out of input
+    InputExhausted {
+        instruction: usize,
+    },
+    DivideByZero {
+        instruction: usize,
+    },
+    /// `mod` of a negative number, or by a number that isn't positive
+    InvalidModulo {
+        instruction: usize,
+    },
+    /// An `add`, `mul` or `div` whose result doesn't fit in an `i64`
+    Overflow {
+        instruction: usize,
+    },
+}
+
+impl fmt::Display for AluError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AluError::InputExhausted { instruction } => {
+                write!(f, "Instruction {} ran out of input", instruction + 1)
+            }
+            AluError::DivideByZero { instruction } => {
+                write!(f, "Instruction {} divided by zero", instruction + 1)
+            }
+            AluError::InvalidModulo { instruction } => {
+                write!(f, "Instruction {} took an invalid modulo", instruction + 1)
+            }
+            AluError::Overflow { instruction } => {
+                write!(f, "Instruction {} overflowed", instruction + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AluError {}
+
+/// The submarine's arithmetic logic unit, with four registers that start
+/// out at zero
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Alu {
+    registers: [i64; 4],
+}
+
+impl Alu {
+    pub fn new() -> Self {
+        Alu::default()
+    }
+
+    pub fn get(&self, register: Register) -> i64 {
+        self.registers[register.index()]
+    }
+
+    fn value(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(r) => self.get(r),
+            Operand::Literal(n) => n,
+        }
+    }
+
+    /// Run `program`, which takes its `inp` values from `input` in order
+    pub fn run<I: IntoIterator<Item = i64>>(
+        &mut self,
+        program: &[Instruction],
+        input: I,
+    ) -> Result<(), AluError> {
+        let mut input = input.into_iter();
+        for (i, instruction) in program.iter().enumerate() {
+            let overflow = AluError::Overflow { instruction: i };
+            let (a, result) = match *instruction {
+                Instruction::Inp(a) => (
+                    a,
+                    input
+                        .next()
+                        .ok_or(AluError::InputExhausted { instruction: i })?,
+                ),
+                Instruction::Add(a, b) => {
+                    (a, self.get(a).checked_add(self.value(b)).ok_or(overflow)?)
+                }
+                Instruction::Mul(a, b) => {
+                    (a, self.get(a).checked_mul(self.value(b)).ok_or(overflow)?)
+                }
+                Instruction::Div(a, b) => match self.value(b) {
+                    0 => return Err(AluError::DivideByZero { instruction: i }),
+                    // Rust's division truncates toward zero, just like the ALU's
+                    b => (a, self.get(a).checked_div(b).ok_or(overflow)?),
+                },
+                Instruction::Mod(a, b) => match (self.get(a), self.value(b)) {
+                    (a_val, b_val) if a_val < 0 || b_val <= 0 => {
+                        return Err(AluError::InvalidModulo { instruction: i })
+                    }
+                    (a_val, b_val) => (a, a_val % b_val),
+                },
+                Instruction::Eql(a, b) => (a, (self.get(a) == self.value(b)) as i64),
+            };
+            self.registers[a.index()] = result;
+        }
+        Ok(())
+    }
+}
+
+fn parse_register(parser: &mut LineParser) -> Result<Register, ParseError> {
+    let name = parser.char_matching("a register (w, x, y or z)", |c| {
+        matches!(c, 'w' | 'x' | 'y' | 'z')
+    })?;
+    Ok(match name {
+        'w' => Register::W,
+        'x' => Register::X,
+        'y' => Register::Y,
+        _ => Register::Z,
+    })
+}
+
+fn parse_operand(parser: &mut LineParser) -> Result<Operand, ParseError> {
+    match parser.peek() {
+        Some(c) if c.is_ascii_alphabetic() => parse_register(parser).map(Operand::Register),
+        _ => parser.integer().map(Operand::Literal),
+    }
+}
+
+fn parse_instruction(parser: &mut LineParser) -> Result<Instruction, ParseError> {
+    if parser.accept("inp ") {
+        let a = parse_register(parser)?;
+        parser.end()?;
+        return Ok(Instruction::Inp(a));
+    }
+
+    let op: fn(Register, Operand) -> Instruction = if parser.accept("add ") {
+        Instruction::Add
+    } else if parser.accept("mul ") {
+        Instruction::Mul
+    } else if parser.accept("div ") {
+        Instruction::Div
+    } else if parser.accept("mod ") {
+        Instruction::Mod
+    } else if parser.accept("eql ") {
+        Instruction::Eql
+    } else {
+        return Err(parser.error("an instruction (inp, add, mul, div, mod or eql)"));
+    };
+    let a = parse_register(parser)?;
+    parser.expect(" ")?;
+    let b = parse_operand(parser)?;
+    parser.end()?;
+    Ok(op(a, b))
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Vec<Instruction>, ParseError> {
+    let program = data
+        .enumerate()
+        .map(|(i, line)| parse_instruction(&mut LineParser::new(i + 1, &line)))
+        .collect::<Result<Vec<Instruction>, ParseError>>()?;
+    if program.is_empty() {
+        return Err(ParseError::end_of_input(1, "an instruction"));
+    }
+    Ok(program)
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Instruction>> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Instruction>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<Vec<Instruction>> {
+    parse_reader(data.as_bytes())
+}
+
+/// The parameters of one digit's block of the MONAD program. Each block
+/// treats `z` as a stack of base-26 digits: it pops the top off if `div`
+/// is 26, and pushes `w + push_offset` unless `w` equals the popped value
+/// plus `check_offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Block {
+    div: i64,
+    check_offset: i64,
+    push_offset: i64,
+}
+
+/// Every block of MONAD is these instructions, apart from the three
+/// literal parameters marked with `None`
+fn block_template() -> [(&'static str, Option<i64>); 18] {
+    [
+        ("inp w", Some(0)),
+        ("mul x 0", Some(0)),
+        ("add x z", Some(0)),
+        ("mod x 26", Some(0)),
+        ("div z", None),
+        ("add x", None),
+        ("eql x w", Some(0)),
+        ("eql x 0", Some(0)),
+        ("mul y 0", Some(0)),
+        ("add y 25", Some(0)),
+        ("mul y x", Some(0)),
+        ("add y 1", Some(0)),
+        ("mul z y", Some(0)),
+        ("mul y 0", Some(0)),
+        ("add y w", Some(0)),
+        ("add y", None),
+        ("mul y x", Some(0)),
+        ("add z y", Some(0)),
+    ]
+}
+
+/// The text of a MONAD program, given the `(div, check_offset,
+/// push_offset)` parameters of each of its blocks
+pub(crate) fn monad_source(params: &[(i64, i64, i64)]) -> String {
+    let mut lines = Vec::with_capacity(params.len() * 18);
+    for (div, check_offset, push_offset) in params {
+        for (text, fixed) in block_template() {
+            lines.push(match (fixed, text) {
+                (Some(_), _) => text.to_string(),
+                (None, "div z") => format!("{} {}", text, div),
+                (None, "add x") => format!("{} {}", text, check_offset),
+                (None, _) => format!("{} {}", text, push_offset),
+            });
+        }
+    }
+    lines.join("\n")
+}
+
+/// Pull the parameters of each block out of a MONAD program
+fn blocks(program: &[Instruction]) -> Result<Vec<Block>> {
+    let template = block_template();
+    if program.len() != template.len() * MODEL_DIGITS {
+        bail!(
+            "MONAD has {} instructions, not {}",
+            program.len(),
+            template.len() * MODEL_DIGITS
+        );
+    }
+
+    let mut blocks = Vec::with_capacity(MODEL_DIGITS);
+    for (b, chunk) in program.chunks(template.len()).enumerate() {
+        let mut params = Vec::with_capacity(3);
+        for (i, (instruction, (expected, fixed))) in chunk.iter().zip(template.iter()).enumerate() {
+            let text = instruction.to_string();
+            let matches = match fixed {
+                Some(_) => text == *expected,
+                None => match instruction {
+                    Instruction::Add(_, Operand::Literal(n))
+                    | Instruction::Div(_, Operand::Literal(n))
+                        if text.starts_with(expected) =>
+                    {
+                        params.push(*n);
+                        true
+                    }
+                    _ => false,
+                },
+            };
+            if !matches {
+                bail!(
+                    "Instruction {} isn't part of MONAD: expected {:?}, found {:?}",
+                    b * template.len() + i + 1,
+                    expected,
+                    text
+                );
+            }
+        }
+        let block = Block {
+            div: params[0],
+            check_offset: params[1],
+            push_offset: params[2],
+        };
+        if block.div != 1 && block.div != 26 {
+            bail!("Block {} divides z by {}, not 1 or 26", b + 1, block.div);
+        }
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+/// Find the largest or smallest model number MONAD accepts. For z to end up
+/// at zero, every block that pops from the stack must not push, which ties
+/// its digit to the digit of the block that pushed the value it pops.
+fn solve(program: &[Instruction], largest: bool) -> Result<u64> {
+    let blocks = blocks(program)?;
+    let mut digits = [0i64; MODEL_DIGITS];
+    let mut stack: Vec<(usize, i64)> = Vec::with_capacity(MODEL_DIGITS);
+    for (i, block) in blocks.iter().enumerate() {
+        if block.div == 1 {
+            stack.push((i, block.push_offset));
+            continue;
+        }
+        let (j, push_offset) = stack
+            .pop()
+            .ok_or_else(|| anyhow!("Block {} pops from an empty stack", i + 1))?;
+        // digits[i] == digits[j] + diff
+        let diff = push_offset + block.check_offset;
+        if diff.abs() > 8 {
+            bail!("Digits {} and {} can never be {} apart", j + 1, i + 1, diff);
+        }
+        digits[j] = if largest {
+            9.min(9 - diff)
+        } else {
+            1.max(1 - diff)
+        };
+        digits[i] = digits[j] + diff;
+    }
+    if !stack.is_empty() {
+        bail!("Blocks push more than they pop, so z can't end up at zero");
+    }
+
+    let mut alu = Alu::new();
+    alu.run(program, digits.iter().copied())?;
+    if alu.get(Register::Z) != 0 {
+        bail!("MONAD rejected the model number it was solved for");
+    }
+    Ok(digits.iter().fold(0, |n, d| n * 10 + *d as u64))
+}
+
+pub fn star1(program: &[Instruction]) -> Result<u64> {
+    solve(program, true)
+}
+
+pub fn star2(program: &[Instruction]) -> Result<u64> {
+    solve(program, false)
+}
+
+pub struct Day24;
+
+impl Puzzle for Day24 {
+    const DAY: u8 = 24;
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEGATE: &str = "inp x\nmul x -1";
+
+    const THREE_TIMES: &str = "inp z\ninp x\nmul z 3\neql z x";
+
+    const BINARY: [&str; 11] = [
+        "inp w", "add z w", "mod z 2", "div w 2", "add y w", "mod y 2", "div w 2", "add x w",
+        "mod x 2", "div w 2", "mod w 2",
+    ];
+
+    /// (div, check_offset, push_offset) for each block of a MONAD-shaped
+    /// program
+    const MONAD_PARAMS: [(i64, i64, i64); MODEL_DIGITS] = [
+        (1, 11, 7),
+        (1, 12, 12),
+        (1, 14, 4),
+        (26, -1, 9),
+        (1, 10, 15),
+        (1, 13, 8),
+        (26, -10, 2),
+        (1, 15, 6),
+        (1, 10, 1),
+        (26, -1, 3),
+        (26, -1, 11),
+        (26, -23, 5),
+        (26, -11, 14),
+        (26, -11, 1),
+    ];
+
+    fn monad() -> String {
+        monad_source(&MONAD_PARAMS)
+    }
+
+    fn run(program: &str, input: &[i64]) -> Result<Alu, AluError> {
+        let mut alu = Alu::new();
+        alu.run(&parse_str(program).unwrap(), input.iter().copied())?;
+        Ok(alu)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let program = parse_str(THREE_TIMES).unwrap();
+        assert_eq!(
+            program[3],
+            Instruction::Eql(Register::Z, Operand::Register(Register::X))
+        );
+        assert_eq!(program[2].to_string(), "mul z 3");
+
+        let err = parse_str("inp w\nadd x -\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 8));
+
+        let err = parse_str("inp w\nsub x 1\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(err
+            .to_string()
+            .starts_with("line 2, column 1: expected an instruction"));
+
+        assert!(parse_str("").is_err());
+        assert!(parse_str("inp 3\n").is_err());
+        assert!(parse_str("inp w x\n").is_err());
+        assert!(parse_str("add q 1\n").is_err());
+        assert!(parse_str("add x\n").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = BINARY.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_alu() {
+        assert_eq!(run(NEGATE, &[7]).unwrap().get(Register::X), -7);
+        assert_eq!(run(THREE_TIMES, &[3, 9]).unwrap().get(Register::Z), 1);
+        assert_eq!(run(THREE_TIMES, &[3, 8]).unwrap().get(Register::Z), 0);
+
+        let alu = run(&BINARY.join("\n"), &[13]).unwrap();
+        let bits: Vec<i64> = [Register::W, Register::X, Register::Y, Register::Z]
+            .iter()
+            .map(|r| alu.get(*r))
+            .collect();
+        assert_eq!(bits, vec![1, 1, 0, 1]);
+
+        assert_eq!(
+            run("div x -2\ninp x\ndiv x -2", &[7])
+                .unwrap()
+                .get(Register::X),
+            -3
+        );
+        assert_eq!(
+            run(THREE_TIMES, &[3]),
+            Err(AluError::InputExhausted { instruction: 1 })
+        );
+        assert_eq!(
+            run("div x y", &[]),
+            Err(AluError::DivideByZero { instruction: 0 })
+        );
+        assert_eq!(
+            run("add x -1\nmod x 2", &[]),
+            Err(AluError::InvalidModulo { instruction: 1 })
+        );
+        assert_eq!(
+            run("add x 9223372036854775807\nadd x 1", &[]),
+            Err(AluError::Overflow { instruction: 1 })
+        );
+        assert_eq!(
+            run("add x 4294967296\nmul x x", &[]),
+            Err(AluError::Overflow { instruction: 1 })
+        );
+        assert_eq!(
+            run("add x -9223372036854775808\ndiv x -1", &[]),
+            Err(AluError::Overflow { instruction: 1 })
+        );
+        assert_eq!(
+            run("add x -9223372036854775808\nadd x 9223372036854775807", &[])
+                .map(|alu| alu.get(Register::X)),
+            Ok(-1)
+        );
+    }
+
+    #[test]
+    fn test_structure() {
+        let mut program = parse_str(&monad()).unwrap();
+        assert_eq!(blocks(&program).unwrap().len(), MODEL_DIGITS);
+
+        program[20] = Instruction::Add(Register::X, Operand::Register(Register::Y));
+        let err = blocks(&program).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Instruction 21 isn't part of MONAD"));
+
+        program.pop();
+        assert!(blocks(&program).is_err());
+        assert!(star1(&parse_str(NEGATE).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_star1() {
+        let program = parse_str(&monad()).unwrap();
+        assert_eq!(star1(&program).unwrap(), 98699974999195);
+
+        // Any larger digit in a pushing block is rejected
+        let mut alu = Alu::new();
+        let digits = [9, 9, 6, 9, 9, 9, 7, 4, 9, 9, 9, 1, 9, 5];
+        alu.run(&program, digits.iter().copied()).unwrap();
+        assert_ne!(alu.get(Register::Z), 0);
+    }
+
+    #[test]
+    fn test_star2() {
+        let program = parse_str(&monad()).unwrap();
+        assert_eq!(star2(&program).unwrap(), 51149311116121);
+    }
+}
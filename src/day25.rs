@@ -0,0 +1,325 @@
+use anyhow::{bail, Result};
+use std::fmt;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::parser::{LineParser, ParseError};
+use crate::puzzle::Puzzle;
+
+const DATA_FILE: &str = "25.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Herd {
+    East,
+    South,
+}
+
+/// The sea floor, which wraps around at its edges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeaFloor {
+    cells: Grid<Option<Herd>>,
+}
+
+impl SeaFloor {
+    pub fn new(cells: Grid<Option<Herd>>) -> Self {
+        SeaFloor { cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Herd> {
+        self.cells.get(x, y).copied().flatten()
+    }
+
+    /// Move every member of `herd` that is facing an empty cell, all at
+    /// once, returning how many moved
+    fn move_herd(&mut self, herd: Herd) -> usize {
+        let (width, height) = (self.width(), self.height());
+        let moves: Vec<((usize, usize), (usize, usize))> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| **cell == Some(herd))
+            .map(|((x, y), _)| {
+                let to = match herd {
+                    Herd::East => ((x + 1) % width, y),
+                    Herd::South => (x, (y + 1) % height),
+                };
+                ((x, y), to)
+            })
+            .filter(|(_, to)| self.cells[*to].is_none())
+            .collect();
+        for (from, to) in &moves {
+            self.cells[*from] = None;
+            self.cells[*to] = Some(herd);
+        }
+        moves.len()
+    }
+
+    /// Carry out one step, the east-facing herd moving before the
+    /// south-facing one, and return how many sea cucumbers moved
+    pub fn step(&mut self) -> usize {
+        self.move_herd(Herd::East) + self.move_herd(Herd::South)
+    }
+
+    /// The state after each step, numbered from 1, up to and including the
+    /// first step in which nothing moves, if there is one
+    pub fn steps(&self) -> Steps {
+        Steps {
+            floor: self.clone(),
+            step: 0,
+            settled: false,
+        }
+    }
+
+    /// The first step in which no sea cucumber moves, or an error if the
+    /// sea cucumbers go round in circles instead
+    pub fn settle(&self) -> Result<usize> {
+        let mut floor = self.clone();
+        // Compare each step against the state at the last power of two, so
+        // that a cycle is caught within twice its length
+        let mut saved = floor.clone();
+        for step in 1.. {
+            if floor.step() == 0 {
+                return Ok(step);
+            }
+            if floor == saved {
+                bail!(
+                    "The sea cucumbers never stop moving: step {} repeats an earlier step",
+                    step
+                );
+            }
+            if step.is_power_of_two() {
+                saved = floor.clone();
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl fmt::Display for SeaFloor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.cells.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                let c = match cell {
+                    Some(Herd::East) => '>',
+                    Some(Herd::South) => 'v',
+                    None => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over snapshots of the sea floor after each step
+#[derive(Debug, Clone)]
+pub struct Steps {
+    floor: SeaFloor,
+    step: usize,
+    settled: bool,
+}
+
+impl Iterator for Steps {
+    type Item = (usize, SeaFloor);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.settled {
+            return None;
+        }
+        self.step += 1;
+        self.settled = self.floor.step() == 0;
+        Some((self.step, self.floor.clone()))
+    }
+}
+
+fn parse_row(
+    parser: &mut LineParser,
+    width: Option<usize>,
+) -> Result<Vec<Option<Herd>>, ParseError> {
+    let mut row = Vec::with_capacity(width.unwrap_or(140));
+    while width.map_or(!parser.is_empty(), |w| row.len() < w) {
+        let c = parser.char_matching("'>', 'v' or '.'", |c| matches!(c, '>' | 'v' | '.'))?;
+        row.push(match c {
+            '>' => Some(Herd::East),
+            'v' => Some(Herd::South),
+            _ => None,
+        });
+    }
+    if row.is_empty() {
+        return Err(parser.error("'>', 'v' or '.'"));
+    }
+    parser.end()?;
+    Ok(row)
+}
+
+pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<SeaFloor, ParseError> {
+    let mut rows: Vec<Vec<Option<Herd>>> = Vec::with_capacity(140);
+    for (i, line) in data.enumerate() {
+        let width = rows.first().map(|r| r.len());
+        rows.push(parse_row(&mut LineParser::new(i + 1, &line), width)?);
+    }
+    if rows.is_empty() {
+        return Err(ParseError::end_of_input(1, "a map of sea cucumbers"));
+    }
+    let cells = Grid::from_rows(rows).expect("Rows are all the same width");
+    Ok(SeaFloor::new(cells))
+}
+
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<SeaFloor> {
+    let data_file = data_dir.as_ref().join(DATA_FILE);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<SeaFloor> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(parse(lines.into_iter())?)
+}
+
+pub fn parse_str(data: &str) -> Result<SeaFloor> {
+    parse_reader(data.as_bytes())
+}
+
+pub fn star1(floor: &SeaFloor) -> Result<usize> {
+    floor.settle()
+}
+
+pub struct Day25;
+
+impl Puzzle for Day25 {
+    const DAY: u8 = 25;
+    type Input = SeaFloor;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    // The last day only has the one puzzle; its second star is awarded for
+    // finishing all the others
+    fn star2(_input: &Self::Input) -> Result<Answer> {
+        Ok("Merry Christmas!".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: [&str; 9] = [
+        "v...>>.vv>",
+        ".vv>>.vv..",
+        ">>.>v>...v",
+        ">>v>>.>.v.",
+        "v>v.vv.v..",
+        ">.>>..v...",
+        ".vv..>.>v.",
+        "v.v..>>v.v",
+        "....v..v.>",
+    ];
+
+    #[test]
+    fn test_parse_errors() {
+        let floor = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!((floor.width(), floor.height()), (10, 9));
+        assert_eq!(floor.get(4, 0), Some(Herd::East));
+        assert_eq!(floor.get(0, 0), Some(Herd::South));
+        assert_eq!(floor.get(1, 0), None);
+        assert_eq!(floor.to_string(), SAMPLE_DATA.join("\n"));
+
+        let err = parse_str("v..\n.>x\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        assert!(parse_str("").is_err());
+        assert!(parse_str("v..\n.>\n").is_err());
+        assert!(parse_str("v..\n.>..\n").is_err());
+
+        // No truncation of the sample input may panic
+        let sample = SAMPLE_DATA.join("\n");
+        for end in 0..sample.len() {
+            let _ = parse_str(&sample[..end]);
+        }
+    }
+
+    #[test]
+    fn test_step() {
+        // The east herd moves first, so it takes the cell the south herd
+        // was about to move into
+        let mut floor = parse_str(">.\n.v\n").unwrap();
+        assert_eq!(floor.step(), 1);
+        assert_eq!(floor.to_string(), ".>\n.v");
+
+        // Both herds wrap around the edges
+        floor = parse_str("...>\nv...\n....\n").unwrap();
+        floor.step();
+        assert_eq!(floor.to_string(), ">...\n....\nv...");
+
+        let floor = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        let (step, first) = floor.steps().next().unwrap();
+        assert_eq!(step, 1);
+        assert_eq!(
+            first.to_string(),
+            [
+                "....>.>v.>",
+                "v.v>.>v.v.",
+                ">v>>..>v..",
+                ">>v>v>.>.v",
+                ".>v.v...v.",
+                "v>>.>vvv..",
+                "..v...>>..",
+                "vv...>>vv.",
+                ">.v.v..v.v",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_star1() {
+        let floor = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        assert_eq!(star1(&floor).unwrap(), 58);
+
+        let snapshots: Vec<(usize, SeaFloor)> = floor.steps().collect();
+        assert_eq!(snapshots.len(), 58);
+        assert_eq!(snapshots[56].1, snapshots[57].1);
+        assert_eq!(
+            snapshots[57].1.to_string(),
+            [
+                "..>>v>vv..",
+                "..v.>>vv..",
+                "..>>v>>vv.",
+                "..>>>>>vv.",
+                "v......>vv",
+                "v>v....>>v",
+                "vvv.....>>",
+                ">vv......>",
+                ".>v.vv.v..",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_never_settles() {
+        // A sea cucumber with nothing in its way goes round forever
+        for data in [">.\n", "..>.\n...v\n", ">..v.\n.v>..\n"] {
+            let floor = parse_str(data).unwrap();
+            assert!(star1(&floor).is_err(), "{:?}", data);
+        }
+        assert_eq!(star1(&parse_str(">>\nvv\n").unwrap()).unwrap(), 1);
+    }
+}
@@ -1,11 +1,17 @@
 use anyhow::Result;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "3.txt";
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<u32>> {
     data.lines()
         .map(|s_res| {
             s_res
@@ -15,6 +21,10 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
         .collect()
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<u32>> {
+    parse_reader(data.as_bytes())
+}
+
 fn bit_set(word: u32, index: u8) -> bool {
     (word & (1 << (31 - index))) != 0
 }
@@ -52,13 +62,15 @@ pub fn star1(data: &[u32]) -> u64 {
     (gamma as u64) * (epsilon as u64)
 }
 
-#[allow(dead_code)]
-fn search_linear(data: &[u32], bit_pattern: u32, bit_offset: u8, greater: bool) -> u32 {
-    if bit_offset == 32 || data.len() == 1 {
+pub(crate) fn search_linear(data: &[u32], bit_pattern: u32, bit_offset: u8, greater: bool) -> u32 {
+    if data.len() == 1 {
+        return data[0];
+    }
+    if bit_offset == 32 {
         return bit_pattern;
     }
     // Need to mask for only the bits we've already considered
-    let offset_mask = std::u32::MAX << (32 - bit_offset);
+    let offset_mask = u32::MAX << (32 - bit_offset);
     let (total, count) = data
         .iter()
         .filter(|&&w| (w & offset_mask) == (bit_pattern & offset_mask))
@@ -79,33 +91,6 @@ fn search_linear(data: &[u32], bit_pattern: u32, bit_offset: u8, greater: bool)
     search_linear(data, new_bit_pattern, bit_offset + 1, greater)
 }
 
-#[allow(dead_code)]
-fn search(data: &mut [u32], bit_offset: u8, greater: bool) -> u32 {
-    // Recursion terminating condition - we've found the entry with
-    // either the most common bit for its field, or the least common
-    if data.len() == 1 {
-        return data[0];
-    }
-
-    // split_point is the last value in the sorted list with a 0
-    // in the requisite position
-    let split_point = data.partition_point(|&x| !bit_set(x, bit_offset));
-    let (zeros, ones) = data.split_at_mut(split_point);
-
-    // Figure out which group is bigger - bit-set vs bit-unset
-    let winner = match (ones.len() >= zeros.len(), greater) {
-        (true, true) => ones,
-        (false, false) => ones,
-        (true, false) => zeros,
-        (false, true) => zeros,
-    };
-
-    // Sort the subset of the array by whether the bit field of interest
-    // is set or not
-    winner.sort_unstable_by_key(|x| bit_set(*x, bit_offset + 1));
-    search(winner, bit_offset + 1, greater)
-}
-
 pub fn star2(data: &mut [u32]) -> u64 {
     let bit_offset = 32 - 12;
 
@@ -117,6 +102,25 @@ pub fn star2(data: &mut [u32]) -> u64 {
     (o2 as u64) * (co2 as u64)
 }
 
+pub struct Day3;
+
+impl Puzzle for Day3 {
+    const DAY: u8 = 3;
+    type Input = Vec<u32>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(&mut input.clone()).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +130,51 @@ mod tests {
         0b00010, 0b01010,
     ];
 
+    fn search(data: &mut [u32], bit_offset: u8, greater: bool) -> u32 {
+        // Recursion terminating condition - we've found the entry with
+        // either the most common bit for its field, or the least common
+        if data.len() == 1 {
+            return data[0];
+        }
+        // Nothing is left to find, or every bit has been looked at and the
+        // entries remaining are all the same
+        if data.is_empty() || bit_offset == 32 {
+            return data.first().copied().unwrap_or(0);
+        }
+
+        // split_point is the last value in the sorted list with a 0
+        // in the requisite position
+        let split_point = data.partition_point(|&x| !bit_set(x, bit_offset));
+        let (zeros, ones) = data.split_at_mut(split_point);
+
+        // Figure out which group is bigger - bit-set vs bit-unset. When every
+        // entry has the same bit, that bit doesn't narrow anything down.
+        let winner = match (ones.len() >= zeros.len(), greater) {
+            _ if zeros.is_empty() => ones,
+            _ if ones.is_empty() => zeros,
+            (true, true) => ones,
+            (false, false) => ones,
+            (true, false) => zeros,
+            (false, true) => zeros,
+        };
+
+        // Sort the subset of the array by whether the bit field of interest
+        // is set or not
+        if bit_offset < 31 {
+            winner.sort_unstable_by_key(|x| bit_set(*x, bit_offset + 1));
+        }
+        search(winner, bit_offset + 1, greater)
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let data = parse_str(
+            "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n",
+        )
+        .unwrap();
+        assert_eq!(data, SAMPLE_DATA);
+    }
+
     #[test]
     fn test_gamma_epsilon() {
         let (gamma, epsilon) = power_consumption(&SAMPLE_DATA, 5);
@@ -136,7 +185,7 @@ mod tests {
 
     #[test]
     fn test_o2_co2() {
-        let mut sample_data = SAMPLE_DATA.clone();
+        let mut sample_data = SAMPLE_DATA;
         sample_data.sort_unstable_by_key(|x| bit_set(*x, 32 - 5));
         let o2 = search(&mut sample_data, 32 - 5, true);
         let co2 = search(&mut sample_data, 32 - 5, false);
@@ -145,14 +194,24 @@ mod tests {
         assert_eq!((o2 as u64) * (co2 as u64), 230u64);
     }
 
+    #[test]
+    fn test_search_edge_cases() {
+        // Nothing to find
+        assert_eq!(search(&mut [], 32 - 5, true), 0);
+        assert_eq!(search_linear(&[], 0, 32 - 5, true), 0);
+        // Duplicates are still together after the last bit
+        assert_eq!(search(&mut [7, 7], 32 - 5, false), 7);
+        assert_eq!(search_linear(&[7, 7], 0, 32 - 5, false), 7);
+    }
+
     #[test]
     fn test_o2_co2_linear() {
-        let mut sample_data = SAMPLE_DATA.clone();
+        let sample_data = SAMPLE_DATA;
         println!("Searching for O2...");
-        let o2 = search_linear(&mut sample_data, 0, 32 - 5, true);
+        let o2 = search_linear(&sample_data, 0, 32 - 5, true);
         assert_eq!(o2, 0b00010111);
         println!("Searching for CO2...");
-        let co2 = search_linear(&mut sample_data, 0, 32 - 5, false);
+        let co2 = search_linear(&sample_data, 0, 32 - 5, false);
         assert_eq!(co2, 0b00001010);
         println!("Testing life support...");
         assert_eq!((o2 as u64) * (co2 as u64), 230u64);
@@ -2,11 +2,17 @@ use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "4.txt";
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<(Vec<u8>, Vec<Board>)> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let mut data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(mut data: R) -> Result<(Vec<u8>, Vec<Board>)> {
     let mut line = String::with_capacity(50);
 
     // Read called numbers
@@ -37,10 +43,14 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<(Vec<u8>, Vec<Boar
     Ok((called, boards))
 }
 
-fn parse_board(data: &mut std::io::BufReader<std::fs::File>) -> Result<Option<Board>> {
+pub fn parse_str(data: &str) -> Result<(Vec<u8>, Vec<Board>)> {
+    parse_reader(data.as_bytes())
+}
+
+fn parse_board<R: BufRead>(data: &mut R) -> Result<Option<Board>> {
     let cells: Vec<String> = data
         .lines()
-        .filter_map(Result::ok)
+        .map_while(Result::ok)
         .take_while(|s| !s.is_empty())
         .collect();
     let cells: Result<Vec<u8>> = cells
@@ -161,6 +171,27 @@ pub fn star2(called: &[u8], boards: &mut [Board]) -> u32 {
     min_score.expect("Programming error")
 }
 
+pub struct Day4;
+
+impl Puzzle for Day4 {
+    const DAY: u8 = 4;
+    type Input = (Vec<u8>, Vec<Board>);
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        let (called, boards) = input;
+        Ok(star1(called, &mut boards.clone()).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        let (called, boards) = input;
+        Ok(star2(called, &mut boards.clone()).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +215,38 @@ mod tests {
         ],
     ];
 
+    const SAMPLE_DATA: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn test_parse_str() {
+        let (called, boards) = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(called, SAMPLE_CALLED);
+        assert_eq!(boards.len(), SAMPLE_BOARDS.len());
+        for (board, cells) in boards.iter().zip(SAMPLE_BOARDS.iter()) {
+            assert_eq!(board.cells, Board::from(cells.as_slice()).cells);
+        }
+    }
+
     #[test]
     fn test_star1() {
         let mut boards: Vec<Board> = SAMPLE_BOARDS
@@ -192,4 +255,10 @@ mod tests {
             .collect();
         assert_eq!(star1(&SAMPLE_CALLED, &mut boards), 4512);
     }
+
+    #[test]
+    fn test_star2() {
+        let (called, mut boards) = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&called, &mut boards), 1924);
+    }
 }
@@ -2,6 +2,9 @@ use anyhow::Result;
 use std::collections::HashMap;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "5.txt";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -76,18 +79,9 @@ impl Range {
             (self.end.y..=self.start.y).rev().collect()
         };
         if x_range.len() == y_range.len() {
-            Box::new(
-                x_range
-                    .into_iter()
-                    .zip(y_range.into_iter())
-                    .map(Point::from),
-            )
+            Box::new(x_range.into_iter().zip(y_range).map(Point::from))
         } else if x_range.len() == 1 {
-            Box::new(
-                std::iter::repeat(x_range[0])
-                    .zip(y_range.into_iter())
-                    .map(Point::from),
-            )
+            Box::new(std::iter::repeat(x_range[0]).zip(y_range).map(Point::from))
         } else if y_range.len() == 1 {
             Box::new(
                 x_range
@@ -111,12 +105,19 @@ impl Range {
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Range>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<Range>> {
     data.lines()
         .map(|s_res| s_res.map_err(|e| e.into()).and_then(|s| s.parse::<Range>()))
         .collect()
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<Range>> {
+    parse_reader(data.as_bytes())
+}
+
 pub fn star1(data: &[Range]) -> u32 {
     let mut occupied_points: HashMap<Point, u32> = HashMap::with_capacity(256);
     for point in data
@@ -139,11 +140,43 @@ pub fn star2(data: &[Range]) -> u32 {
     occupied_points.values().filter(|&x| *x >= 2).count() as u32
 }
 
+pub struct Day5;
+
+impl Puzzle for Day5 {
+    const DAY: u8 = 5;
+    type Input = Vec<Range>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [(Point, Point); 10] = [
+    const SAMPLE_DATA: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+    const SAMPLE_POINTS: [(Point, Point); 10] = [
         (Point { x: 0, y: 9 }, Point { x: 5, y: 9 }),
         (Point { x: 8, y: 0 }, Point { x: 0, y: 8 }),
         (Point { x: 9, y: 4 }, Point { x: 3, y: 4 }),
@@ -156,16 +189,26 @@ mod tests {
         (Point { x: 5, y: 5 }, Point { x: 8, y: 2 }),
     ];
 
+    #[test]
+    fn test_parse_str() {
+        let sample_data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(sample_data.len(), SAMPLE_POINTS.len());
+        for (range, (start, end)) in sample_data.iter().zip(SAMPLE_POINTS.iter()) {
+            assert_eq!(range.start, *start);
+            assert_eq!(range.end, *end);
+        }
+        assert!(parse_str("0,9 -> 5").is_err());
+    }
+
     #[test]
     fn test_star1() {
-        let sample_data: Vec<Range> = SAMPLE_DATA.iter().cloned().map(Range::from).collect();
+        let sample_data: Vec<Range> = SAMPLE_POINTS.iter().cloned().map(Range::from).collect();
         assert_eq!(star1(&sample_data), 5);
     }
 
-    /*
     #[test]
     fn test_star2() {
-        assert_eq!(star2(&SAMPLE_DATA), 5);
+        let sample_data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&sample_data), 12);
     }
-    */
 }
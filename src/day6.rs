@@ -2,6 +2,9 @@ use anyhow::Result;
 use std::collections::VecDeque;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "6.txt";
 
 #[derive(Debug, Clone)]
@@ -50,7 +53,10 @@ impl FishPond {
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u8>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<u8>> {
     data.split(b',')
         .map(|b_res| {
             b_res
@@ -61,20 +67,44 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u8>> {
         .collect()
 }
 
-pub fn star1(data: &[u8]) -> u64 {
+pub fn parse_str(data: &str) -> Result<Vec<u8>> {
+    parse_reader(data.as_bytes())
+}
+
+/// How many lanternfish there are after `days` days
+pub(crate) fn population(data: &[u8], days: u32) -> u64 {
     let mut pond = FishPond::from(data);
-    for _ in 1..=80 {
+    for _ in 1..=days {
         pond.tick();
     }
     pond.count()
 }
 
+pub fn star1(data: &[u8]) -> u64 {
+    population(data, 80)
+}
+
 pub fn star2(data: &[u8]) -> u64 {
-    let mut pond = FishPond::from(data);
-    for _ in 1..=256 {
-        pond.tick();
+    population(data, 256)
+}
+
+pub struct Day6;
+
+impl Puzzle for Day6 {
+    const DAY: u8 = 6;
+    type Input = Vec<u8>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
     }
-    pond.count()
 }
 
 #[cfg(test)]
@@ -83,6 +113,12 @@ mod tests {
 
     const SAMPLE_DATA: [u8; 5] = [3, 4, 3, 1, 2];
 
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(parse_str("3,4,3,1,2\n").unwrap(), SAMPLE_DATA);
+        assert!(parse_str("3,4,x").is_err());
+    }
+
     #[test]
     fn test_star1() {
         let mut pond = FishPond::from(SAMPLE_DATA.as_slice());
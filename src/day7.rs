@@ -1,11 +1,17 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "7.txt";
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<u32>> {
     let mut result: Vec<u32> = data
         .split(b',')
         .map(|b_res| {
@@ -19,6 +25,10 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<u32>> {
     Ok(result)
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<u32>> {
+    parse_reader(data.as_bytes())
+}
+
 fn abs_difference<T: std::ops::Sub<Output = T> + Ord>(x: T, y: T) -> T {
     if x < y {
         y - x
@@ -47,44 +57,73 @@ fn score_solution_triangular(data: &[u32], target: u32) -> u32 {
     score
 }
 
-fn optimize_score<F: Fn(u32) -> u32>(data: &[u32], scoring_function: F) -> u32 {
+fn optimize_score<F: Fn(u32) -> u32>(data: &[u32], scoring_function: F) -> Result<u32> {
     let mut min_data: Vec<u32> = Vec::from(data);
     min_data.dedup();
-    if let Some(window) = min_data.windows(3).find(|window| {
-        scoring_function(window[0]) > scoring_function(window[1])
-            && scoring_function(window[1]) < scoring_function(window[2])
-    }) {
-        (window[0]..=window[2]).map(scoring_function).min().unwrap()
-    } else {
-        panic!();
-    }
+    // Scores are convex, so the best target lies between the positions on
+    // either side of the best-scoring crab position
+    let best = (0..min_data.len())
+        .min_by_key(|&i| scoring_function(min_data[i]))
+        .ok_or_else(|| anyhow!("There are no crabs to align"))?;
+    let lower = min_data[best.saturating_sub(1)];
+    let upper = min_data[(best + 1).min(min_data.len() - 1)];
+    Ok((lower..=upper).map(scoring_function).min().unwrap())
 }
 
-pub fn star1(data: &[u32]) -> u32 {
+pub fn star1(data: &[u32]) -> Result<u32> {
     optimize_score(data, |target| score_solution_linear(data, target))
 }
 
-pub fn star2(data: &[u32]) -> u32 {
+pub fn star2(data: &[u32]) -> Result<u32> {
     optimize_score(data, |target| score_solution_triangular(data, target))
 }
 
+pub struct Day7;
+
+impl Puzzle for Day7 {
+    const DAY: u8 = 7;
+    type Input = Vec<u32>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input)?.into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [u32; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+    const SAMPLE_DATA: &str = "16,1,2,0,4,2,7,1,2,14\n";
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(
+            parse_str(SAMPLE_DATA).unwrap(),
+            vec![0, 1, 1, 2, 2, 2, 4, 7, 14, 16]
+        );
+    }
 
     #[test]
     fn test_star1() {
-        let mut sample_data = Vec::from(SAMPLE_DATA);
-        sample_data.sort_unstable();
-        assert_eq!(star1(&sample_data), 37);
+        let sample_data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star1(&sample_data).unwrap(), 37);
+
+        let no_crabs = parse_str("").unwrap();
+        assert!(star1(&no_crabs).is_err());
+        assert!(star2(&no_crabs).is_err());
     }
 
     #[test]
     fn test_star2() {
-        let mut sample_data = Vec::from(SAMPLE_DATA);
-        sample_data.sort();
-        assert_eq!(star2(&sample_data), 168);
+        let sample_data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&sample_data).unwrap(), 168);
     }
 }
@@ -3,6 +3,9 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "8.txt";
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -172,7 +175,10 @@ impl SevSegMapping {
 
 pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<SevSegMapping>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
+}
+
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Vec<SevSegMapping>> {
     let result = data
         .lines()
         .map(|b_res| {
@@ -184,6 +190,10 @@ pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<SevSegMapping>
     Ok(result)
 }
 
+pub fn parse_str(data: &str) -> Result<Vec<SevSegMapping>> {
+    parse_reader(data.as_bytes())
+}
+
 fn count_unique(data: &[SevSegMapping]) -> u32 {
     data.iter()
         .flat_map(|m| m.display.iter())
@@ -199,12 +209,31 @@ pub fn star2(data: &[SevSegMapping]) -> u32 {
     data.iter().map(|ssm| ssm.solve()).sum::<u32>()
 }
 
+pub struct Day8;
+
+impl Puzzle for Day8 {
+    const DAY: u8 = 8;
+    type Input = Vec<SevSegMapping>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Result;
 
-    const SAMPLE_DATA: [&'static str; 10] = [
+    const SAMPLE_DATA: [&str; 10] = [
         "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe",
         "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
         "fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg",
@@ -218,24 +247,30 @@ mod tests {
     ];
 
     #[test]
-    fn test_star1() {
+    fn test_parse_str() {
         let sample_data: Result<Vec<SevSegMapping>> = SAMPLE_DATA
             .iter()
             .cloned()
             .map(SevSegMapping::try_from)
             .collect();
         let sample_data = sample_data.unwrap();
+        let parsed = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
+        for (a, b) in sample_data.iter().zip(parsed.iter()) {
+            assert_eq!(a.digits, b.digits);
+            assert_eq!(a.display, b.display);
+        }
+        assert!(parse_str("be cfbegad cbdgef").is_err());
+    }
+
+    #[test]
+    fn test_star1() {
+        let sample_data = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star1(&sample_data), 26);
     }
 
     #[test]
     fn test_star2() {
-        let sample_data: Result<Vec<SevSegMapping>> = (SAMPLE_DATA)
-            .iter()
-            .cloned()
-            .map(SevSegMapping::try_from)
-            .collect();
-        let sample_data = sample_data.unwrap();
+        let sample_data = parse_str(&SAMPLE_DATA.join("\n")).unwrap();
         assert_eq!(star2(&sample_data), 61229);
     }
 }
@@ -2,81 +2,64 @@ use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::puzzle::Puzzle;
+
 const DATA_FILE: &str = "9.txt";
 
-pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Vec<Vec<u8>>> {
+pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Grid<u8>> {
     let data_file = data_dir.as_ref().join(DATA_FILE);
-    let data = std::io::BufReader::new(std::fs::File::open(&data_file)?);
-    let result = data
-        .lines()
-        .map(|b_res| {
-            b_res
-                .map(|s| s.bytes().map(|b| b - b'0').collect::<Vec<u8>>())
-                .map_err(|e| e.into())
-        })
-        .collect::<Result<Vec<Vec<u8>>>>()?;
-    Ok(result)
+    parse_reader(std::io::BufReader::new(std::fs::File::open(&data_file)?))
 }
 
-fn get_adjacents(data: &[&[u8]], point: &(usize, usize)) -> HashSet<(usize, usize)> {
-    let (y, x) = point;
-    let mut adjacents: HashSet<(usize, usize)> = HashSet::with_capacity(4);
+pub fn parse_reader<R: BufRead>(data: R) -> Result<Grid<u8>> {
+    let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+    Ok(Grid::parse_digits(lines.into_iter(), "a height", 0..=9)?)
+}
 
-    if let Some(new_x) = x.checked_sub(1) {
-        adjacents.insert((*y, new_x));
-    }
-    if data.get(*y).and_then(|r| r.get(x + 1)).is_some() {
-        adjacents.insert((*y, x + 1));
-    }
-    if let Some(new_y) = y.checked_sub(1) {
-        adjacents.insert((new_y, *x));
-    }
-    if data.get(y + 1).and_then(|r| r.get(*x)).is_some() {
-        adjacents.insert((y + 1, *x));
-    }
-    adjacents
+pub fn parse_str(data: &str) -> Result<Grid<u8>> {
+    parse_reader(data.as_bytes())
 }
 
-fn low_points(data: &[&[u8]]) -> HashMap<(usize, usize), u8> {
+fn low_points(data: &Grid<u8>) -> HashMap<(usize, usize), u8> {
     let mut points: HashMap<(usize, usize), u8> = HashMap::new();
-    for (y, row) in data.iter().enumerate() {
-        for (x, val) in row.iter().enumerate() {
-            if get_adjacents(data, &(y, x))
-                .iter()
-                .map(|(b, a)| data[*b][*a])
-                .all(|other| val < &other)
-            {
-                points.insert((y, x), *val);
-            }
+    for ((x, y), val) in data.iter() {
+        if data
+            .neighbors4(x, y)
+            .map(|point| data[point])
+            .all(|other| val < &other)
+        {
+            points.insert((x, y), *val);
         }
     }
     points
 }
 
-fn grow_basin(data: &[&[u8]], point: &(usize, usize), basin: &mut HashSet<(usize, usize)>) {
-    let (y, x) = point;
-    if data[*y][*x] >= 9 {
+fn grow_basin(data: &Grid<u8>, point: (usize, usize), basin: &mut HashSet<(usize, usize)>) {
+    if data[point] >= 9 {
         return;
     } else {
-        basin.insert(*point);
+        basin.insert(point);
     }
 
-    for point in get_adjacents(data, &(*y, *x)).iter() {
-        if !basin.contains(point) {
+    let (x, y) = point;
+    for point in data.neighbors4(x, y) {
+        if !basin.contains(&point) {
             grow_basin(data, point, basin);
         }
     }
 }
 
-pub fn star1(data: &[&[u8]]) -> u32 {
+pub fn star1(data: &Grid<u8>) -> u32 {
     low_points(data).values().map(|d| (d + 1) as u32).sum()
 }
 
-pub fn star2(data: &[&[u8]]) -> u32 {
+pub fn star2(data: &Grid<u8>) -> u32 {
     let mut basin_sizes = Vec::with_capacity(10);
     for point in low_points(data).keys() {
         let mut basin: HashSet<(usize, usize)> = HashSet::with_capacity(10);
-        grow_basin(data, point, &mut basin);
+        grow_basin(data, *point, &mut basin);
         basin_sizes.push(basin.len());
     }
     basin_sizes.sort_unstable();
@@ -86,27 +69,60 @@ pub fn star2(data: &[&[u8]]) -> u32 {
         * basin_sizes.pop().unwrap() as u32
 }
 
+pub struct Day9;
+
+impl Puzzle for Day9 {
+    const DAY: u8 = 9;
+    type Input = Grid<u8>;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input> {
+        parse_reader(input)
+    }
+
+    fn star1(input: &Self::Input) -> Result<Answer> {
+        Ok(star1(input).into())
+    }
+
+    fn star2(input: &Self::Input) -> Result<Answer> {
+        Ok(star2(input).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_DATA: [[u8; 10]; 5] = [
-        [2, 1, 9, 9, 9, 4, 3, 2, 1, 0],
-        [3, 9, 8, 7, 8, 9, 4, 9, 2, 1],
-        [9, 8, 5, 6, 7, 8, 9, 8, 9, 2],
-        [8, 7, 6, 7, 8, 9, 6, 7, 8, 9],
-        [9, 8, 9, 9, 9, 6, 5, 6, 7, 8],
-    ];
+    const SAMPLE_DATA: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+    #[test]
+    fn test_parse_str() {
+        let data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(data.height(), 5);
+        assert_eq!(data.row(0), &[2, 1, 9, 9, 9, 4, 3, 2, 1, 0]);
+        assert_eq!(data.row(4), &[9, 8, 9, 9, 9, 6, 5, 6, 7, 8]);
+        assert!(parse_str(
+            "2199
+398
+"
+        )
+        .is_err());
+    }
 
     #[test]
     fn test_star1() {
-        let sample_data: Vec<&[u8]> = SAMPLE_DATA.iter().map(|r| r.as_slice()).collect();
-        assert_eq!(star1(&sample_data), 15);
+        let data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star1(&data), 15);
     }
 
     #[test]
     fn test_star2() {
-        let sample_data: Vec<&[u8]> = SAMPLE_DATA.iter().map(|r| r.as_slice()).collect();
-        assert_eq!(star2(&sample_data), 1134);
+        let data = parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(star2(&data), 1134);
     }
 }
@@ -0,0 +1,231 @@
+//! Differential tests: solvers with more than one strategy, or with an
+//! obvious naive reference algorithm, must agree on generated inputs.
+//!
+//! The reference algorithms here are deliberately the slowest, most literal
+//! reading of each puzzle, so inputs are kept small.
+
+use std::collections::HashMap;
+
+use crate::generate;
+use crate::grid::Grid;
+use crate::{day14, day15, day22, day3, day6, day7};
+
+const SEEDS: u64 = 20;
+
+/// Filter the diagnostic report down one bit at a time, keeping the
+/// numbers with the most (or least) common value of that bit. A bit that
+/// every remaining number shares doesn't narrow anything down.
+fn rating(data: &[u32], width: u8, most_common: bool) -> u32 {
+    let mut remaining = data.to_vec();
+    for bit in (0..width).rev() {
+        if remaining.len() == 1 {
+            break;
+        }
+        let ones = remaining.iter().filter(|n| (*n >> bit) & 1 == 1).count();
+        let zeros = remaining.len() - ones;
+        if ones == 0 || zeros == 0 {
+            continue;
+        }
+        let keep = if most_common {
+            ones >= zeros
+        } else {
+            ones < zeros
+        };
+        remaining.retain(|n| ((n >> bit) & 1 == 1) == keep);
+    }
+    remaining[0]
+}
+
+#[test]
+fn test_day3_search() {
+    for seed in 0..SEEDS {
+        let size = 1 + (seed as usize * 37) % 300;
+        let data = day3::parse_str(&generate::day3(seed, size)).unwrap();
+        let offset = 32 - 12;
+        for most_common in [true, false] {
+            let expected = rating(&data, 12, most_common);
+            assert_eq!(
+                day3::search_linear(&data, 0, offset, most_common),
+                expected,
+                "search_linear, seed {}, most common: {}",
+                seed,
+                most_common
+            );
+        }
+    }
+}
+
+/// Simulate every lanternfish individually
+fn fish_after(data: &[u8], days: u32) -> u64 {
+    let mut fish = data.to_vec();
+    for _ in 0..days {
+        let spawned = fish.iter().filter(|timer| **timer == 0).count();
+        for timer in fish.iter_mut() {
+            *timer = if *timer == 0 { 6 } else { *timer - 1 };
+        }
+        fish.extend(std::iter::repeat(8).take(spawned));
+    }
+    fish.len() as u64
+}
+
+#[test]
+fn test_day6_population() {
+    for seed in 0..SEEDS {
+        let data = day6::parse_str(&generate::day6(seed, 1 + seed as usize % 5)).unwrap();
+        for days in [0, 1, 5, 18, 40, 80] {
+            assert_eq!(
+                day6::population(&data, days),
+                fish_after(&data, days),
+                "seed {}, {} days",
+                seed,
+                days
+            );
+        }
+    }
+}
+
+/// Try every position the crabs could line up on
+fn cheapest_alignment<F: Fn(u32) -> u32>(crabs: &[u32], fuel: F) -> u32 {
+    let (min, max) = (*crabs.iter().min().unwrap(), *crabs.iter().max().unwrap());
+    (min..=max)
+        .map(|target| {
+            crabs
+                .iter()
+                .map(|crab| fuel(crab.max(&target) - crab.min(&target)))
+                .sum()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn test_day7_alignment() {
+    for seed in 0..SEEDS {
+        let crabs = day7::parse_str(&generate::day7(seed, 1 + seed as usize * 3)).unwrap();
+        assert_eq!(
+            day7::star1(&crabs).unwrap(),
+            cheapest_alignment(&crabs, |distance| distance),
+            "seed {}",
+            seed
+        );
+        assert_eq!(
+            day7::star2(&crabs).unwrap(),
+            cheapest_alignment(&crabs, |distance| distance * (distance + 1) / 2),
+            "seed {}",
+            seed
+        );
+    }
+}
+
+/// Build the polymer up one insertion step at a time
+fn expand(template: &[u8], insertions: &HashMap<(u8, u8), u8>, steps: u32) -> u64 {
+    let mut polymer = template.to_vec();
+    for _ in 0..steps {
+        let mut next = Vec::with_capacity(polymer.len() * 2);
+        for pair in polymer.windows(2) {
+            next.push(pair[0]);
+            next.extend(insertions.get(&(pair[0], pair[1])));
+        }
+        next.extend(polymer.last());
+        polymer = next;
+    }
+    let mut counts: HashMap<u8, u64> = HashMap::new();
+    for element in polymer {
+        *counts.entry(element).or_insert(0) += 1;
+    }
+    counts.values().max().unwrap() - counts.values().min().unwrap()
+}
+
+#[test]
+fn test_day14_expansion() {
+    for seed in 0..SEEDS {
+        let size = 2 + seed as usize % 8;
+        let (template, insertions) = day14::parse_str(&generate::day14(seed, size)).unwrap();
+        for steps in [0, 1, 4, 10] {
+            assert_eq!(
+                day14::apply(&template, &insertions, steps),
+                expand(&template, &insertions, steps),
+                "seed {}, {} steps",
+                seed,
+                steps
+            );
+        }
+    }
+}
+
+/// Relax every cell's risk against its neighbours until nothing improves
+fn lowest_risk(maze: &Grid<u8>) -> u32 {
+    let mut risk = Grid::new(maze.width(), maze.height(), u32::MAX);
+    risk[(0, 0)] = 0;
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (x, y) in maze.points() {
+            for (nx, ny) in maze.neighbors4(x, y) {
+                if risk[(nx, ny)] == u32::MAX {
+                    continue;
+                }
+                let via = risk[(nx, ny)] + maze[(x, y)] as u32;
+                if via < risk[(x, y)] {
+                    risk[(x, y)] = via;
+                    changed = true;
+                }
+            }
+        }
+    }
+    risk[(maze.width() - 1, maze.height() - 1)]
+}
+
+#[test]
+fn test_day15_risk() {
+    for seed in 0..SEEDS {
+        let maze = day15::parse_str(&generate::day15(seed, 1 + seed as usize % 12)).unwrap();
+        assert_eq!(day15::star1(&maze), lowest_risk(&maze), "seed {}", seed);
+
+        let (width, height) = (maze.width(), maze.height());
+        let tiled = Grid::from_fn(width * 5, height * 5, |x, y| {
+            let risk = maze[(x % width, y % height)] as usize + x / width + y / height;
+            ((risk - 1) % 9 + 1) as u8
+        });
+        assert_eq!(day15::star2(&maze), lowest_risk(&tiled), "seed {}", seed);
+    }
+}
+
+/// Switch every cube in the initialization region on or off one at a time
+fn cubes_on(steps: &[day22::Step]) -> u64 {
+    const SIDE: usize = 101;
+    let mut reactor = vec![false; SIDE * SIDE * SIDE];
+    for step in steps {
+        let lower = step.cuboid.min().map(|a| a.max(-50));
+        let upper = step.cuboid.max().map(|a| a.min(50));
+        for x in lower[0]..=upper[0] {
+            for y in lower[1]..=upper[1] {
+                for z in lower[2]..=upper[2] {
+                    let index = [x, y, z]
+                        .iter()
+                        .fold(0, |index, a| index * SIDE + (a + 50) as usize);
+                    reactor[index] = step.on;
+                }
+            }
+        }
+    }
+    reactor.iter().filter(|on| **on).count() as u64
+}
+
+#[test]
+fn test_day22_reboot() {
+    for seed in 0..SEEDS {
+        let steps = day22::parse_str(&generate::day22(seed, 1 + seed as usize % 10)).unwrap();
+        assert_eq!(day22::star1(&steps), cubes_on(&steps), "seed {}", seed);
+
+        let mut reactor = day22::CuboidSet::new();
+        for step in &steps {
+            if step.on {
+                reactor.insert(step.cuboid);
+            } else {
+                reactor.remove(&step.cuboid);
+            }
+        }
+        assert_eq!(day22::star2(&steps), reactor.volume(), "seed {}", seed);
+    }
+}
@@ -0,0 +1,761 @@
+//! Seeded generators of random puzzle inputs, written in exactly the text
+//! format each day's parser accepts, for stress tests, benchmarks and
+//! parser round-trip checks beyond the small sample inputs.
+//!
+//! The same day, seed and size always produce the same input. What `size`
+//! measures depends on the day, as described on each generator; days 21, 23
+//! and 24 have a fixed shape and ignore it. Inputs are shaped like the real
+//! ones, but a few puzzles (days 11 and 25) only terminate on well-behaved
+//! inputs, which a random one isn't guaranteed to be.
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::day19::{self, Point};
+use crate::{day24, ocr};
+
+/// A small pseudo-random number generator (SplitMix64). It lives in the
+/// crate so that the inputs generated from a seed never change underneath
+/// recorded benchmarks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, where `n` must not be zero
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Can't pick a number below zero");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as i64
+    }
+
+    /// True with a probability of `numerator / denominator`
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+const GENERATORS: [fn(u64, usize) -> String; 25] = [
+    day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13, day14, day15,
+    day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+];
+
+/// A random input for `day`, or `None` if there is no such day
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let generator = GENERATORS.get((day as usize).checked_sub(1)?)?;
+    Some(generator(seed, size))
+}
+
+/// Lines of an input file, each ending in a newline
+fn lines<I: IntoIterator<Item = String>>(lines: I) -> String {
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// `height` rows of `width` cells, each drawn by `cell`
+fn grid<F: FnMut(&mut Rng) -> char>(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    mut cell: F,
+) -> String {
+    lines(
+        (0..height)
+            .map(|_| (0..width).map(|_| cell(rng)).collect::<String>())
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn joined<T: ToString>(items: &[T], separator: &str) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// `size` sonar depth readings
+pub fn day1(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut depth = rng.range(100..=300);
+    lines((0..size).map(|_| {
+        let reading = depth;
+        depth = (depth + rng.range(-20..=40)).max(0);
+        reading.to_string()
+    }))
+}
+
+/// `size` submarine commands
+pub fn day2(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|_| {
+        let direction = rng.choose(&["forward", "down", "up"]);
+        format!("{} {}", direction, rng.range(1..=9))
+    }))
+}
+
+/// `size` distinct 12-bit diagnostic numbers, up to all 4096 of them
+pub fn day3(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut numbers: Vec<u32> = (0..4096).collect();
+    rng.shuffle(&mut numbers);
+    lines(numbers.iter().take(size).map(|n| format!("{:012b}", n)))
+}
+
+/// Every number from 0 to 99 in a random order, and `size` bingo boards
+pub fn day4(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut numbers: Vec<u8> = (0..100).collect();
+    rng.shuffle(&mut numbers);
+    let mut out = vec![joined(&numbers, ",")];
+    for _ in 0..size {
+        rng.shuffle(&mut numbers);
+        out.push(String::new());
+        for row in numbers[..25].chunks(5) {
+            let row: Vec<String> = row.iter().map(|n| format!("{:>2}", n)).collect();
+            out.push(row.join(" "));
+        }
+    }
+    lines(out)
+}
+
+/// `size` horizontal, vertical and diagonal lines of vents
+pub fn day5(seed: u64, size: usize) -> String {
+    const MAX: i64 = 989;
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|_| {
+        let (x1, y1) = (rng.range(0..=MAX), rng.range(0..=MAX));
+        let (x2, y2) = match rng.below(3) {
+            0 => (rng.range(0..=MAX), y1),
+            1 => (x1, rng.range(0..=MAX)),
+            _ => {
+                let (dx, dy) = (*rng.choose(&[-1, 1]), *rng.choose(&[-1, 1]));
+                let room = |a: i64, d: i64| if d > 0 { MAX - a } else { a };
+                let len = rng.range(0..=room(x1, dx).min(room(y1, dy)));
+                (x1 + dx * len, y1 + dy * len)
+            }
+        };
+        format!("{},{} -> {},{}", x1, y1, x2, y2)
+    }))
+}
+
+/// `size` lanternfish timers
+pub fn day6(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let timers: Vec<i64> = (0..size).map(|_| rng.range(1..=5)).collect();
+    lines(vec![joined(&timers, ",")])
+}
+
+/// `size` crab positions
+pub fn day7(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let positions: Vec<i64> = (0..size).map(|_| rng.range(0..=1999)).collect();
+    lines(vec![joined(&positions, ",")])
+}
+
+/// `size` scrambled seven-segment displays
+pub fn day8(seed: u64, size: usize) -> String {
+    const DIGITS: [&str; 10] = [
+        "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+    ];
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|_| {
+        let mut wires: Vec<u8> = (b'a'..=b'g').collect();
+        rng.shuffle(&mut wires);
+        let scramble = |digit: &str, rng: &mut Rng| {
+            let mut segments: Vec<char> = digit
+                .bytes()
+                .map(|b| wires[(b - b'a') as usize] as char)
+                .collect();
+            rng.shuffle(&mut segments);
+            segments.into_iter().collect::<String>()
+        };
+        let mut patterns: Vec<String> = DIGITS.iter().map(|d| scramble(d, &mut rng)).collect();
+        rng.shuffle(&mut patterns);
+        let display: Vec<String> = (0..4)
+            .map(|_| {
+                let digit = *rng.choose(&DIGITS);
+                scramble(digit, &mut rng)
+            })
+            .collect();
+        format!("{} | {}", patterns.join(" "), display.join(" "))
+    }))
+}
+
+fn digit(rng: &mut Rng, range: RangeInclusive<i64>) -> char {
+    (b'0' + rng.range(range) as u8) as char
+}
+
+/// A `size` by `size` height map
+pub fn day9(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    grid(&mut rng, size, size, |rng| digit(rng, 0..=9))
+}
+
+/// `size` lines of navigation subsystem, each either corrupted or
+/// incomplete. The first line is always incomplete.
+pub fn day10(seed: u64, size: usize) -> String {
+    const OPEN: [char; 4] = ['(', '[', '{', '<'];
+    const CLOSE: [char; 4] = [')', ']', '}', '>'];
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|i| {
+        let len = rng.range(24..=110) as usize;
+        let mut line = String::with_capacity(len + 1);
+        let mut stack: Vec<usize> = Vec::with_capacity(len);
+        while line.len() < len || stack.is_empty() {
+            if !stack.is_empty() && rng.chance(2, 5) {
+                line.push(CLOSE[stack.pop().unwrap()]);
+            } else {
+                let bracket = rng.below(OPEN.len());
+                stack.push(bracket);
+                line.push(OPEN[bracket]);
+            }
+        }
+        if i > 0 && rng.chance(1, 2) {
+            let expected = *stack.last().unwrap();
+            let wrong = (expected + 1 + rng.below(CLOSE.len() - 1)) % CLOSE.len();
+            line.push(CLOSE[wrong]);
+        }
+        line
+    }))
+}
+
+/// A `size` by `size` grid of octopus energy levels
+pub fn day11(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    grid(&mut rng, size, size, |rng| digit(rng, 0..=9))
+}
+
+/// A connected cave system with `size` small caves and about a third as
+/// many big ones. Big caves are never joined to each other, or there would
+/// be infinitely many paths.
+pub fn day12(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut names: HashSet<String> = HashSet::new();
+    let mut name = |rng: &mut Rng, big: bool| loop {
+        let base = if big { b'A' } else { b'a' };
+        let name: String = (0..2)
+            .map(|_| (base + rng.below(26) as u8) as char)
+            .collect();
+        if names.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    let mut caves: Vec<(String, bool)> = Vec::with_capacity(size + size / 3 + 3);
+    for _ in 0..size {
+        caves.push((name(&mut rng, false), false));
+    }
+    for _ in 0..(size / 3 + 1) {
+        caves.push((name(&mut rng, true), true));
+    }
+    rng.shuffle(&mut caves);
+    caves.insert(0, ("start".to_string(), false));
+    caves.push(("end".to_string(), false));
+
+    let mut edges: Vec<(usize, usize)> = Vec::with_capacity(caves.len() * 2);
+    for b in 1..caves.len() {
+        let a = loop {
+            let a = rng.below(b);
+            if !(caves[a].1 && caves[b].1) {
+                break a;
+            }
+        };
+        edges.push((a, b));
+    }
+    for _ in 0..(size / 2) {
+        let (a, b) = (rng.below(caves.len()), rng.below(caves.len()));
+        let (a, b) = (a.min(b), a.max(b));
+        if a != b && !(caves[a].1 && caves[b].1) && !edges.contains(&(a, b)) {
+            edges.push((a, b));
+        }
+    }
+    rng.shuffle(&mut edges);
+    lines(edges.into_iter().map(|(a, b)| {
+        let (a, b) = if rng.chance(1, 2) { (a, b) } else { (b, a) };
+        format!("{}-{}", caves[a].0, caves[b].0)
+    }))
+}
+
+/// Dots that fold up into `size` random capital letters, and the folds
+pub fn day13(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let letters: Vec<char> = ocr::letters().collect();
+    let text: String = (0..size.max(1)).map(|_| *rng.choose(&letters)).collect();
+    let bitmap = ocr::render(&text).expect("The font has every letter it lists");
+
+    // Unfold the paper outwards from the letters, so the folds come out
+    // innermost first
+    let mut axes = [
+        true, true, true, true, false, false, false, false, false, false,
+    ];
+    rng.shuffle(&mut axes);
+    let (mut width, mut height) = (bitmap.width(), ocr::GLYPH_HEIGHT);
+    let mut folds: Vec<(char, usize)> = Vec::with_capacity(axes.len());
+    for along_x in axes {
+        if along_x {
+            folds.push(('x', width));
+            width = width * 2 + 1;
+        } else {
+            folds.push(('y', height));
+            height = height * 2 + 1;
+        }
+    }
+
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    let mut dots: Vec<(usize, usize)> = Vec::new();
+    for (y, row) in bitmap.rows().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, lit)| **lit) {
+            for _ in 0..rng.range(1..=3) {
+                let (mut dot_x, mut dot_y) = (x, y);
+                for (axis, line) in &folds {
+                    if rng.chance(1, 2) {
+                        match axis {
+                            'x' => dot_x = line * 2 - dot_x,
+                            _ => dot_y = line * 2 - dot_y,
+                        }
+                    }
+                }
+                if seen.insert((dot_x, dot_y)) {
+                    dots.push((dot_x, dot_y));
+                }
+            }
+        }
+    }
+    rng.shuffle(&mut dots);
+
+    let mut out: Vec<String> = dots.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+    out.push(String::new());
+    out.extend(
+        folds
+            .iter()
+            .rev()
+            .map(|(axis, line)| format!("fold along {}={}", axis, line)),
+    );
+    lines(out)
+}
+
+/// A polymer template `size` elements long, with an insertion rule for
+/// every pair of its ten elements
+pub fn day14(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut alphabet: Vec<char> = ('A'..='Z').collect();
+    rng.shuffle(&mut alphabet);
+    let elements = &alphabet[..10];
+    let template: String = (0..size.max(2)).map(|_| *rng.choose(elements)).collect();
+    let mut out = vec![template, String::new()];
+    for a in elements {
+        for b in elements {
+            out.push(format!("{}{} -> {}", a, b, rng.choose(elements)));
+        }
+    }
+    lines(out)
+}
+
+/// A `size` by `size` map of risk levels
+pub fn day15(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    grid(&mut rng, size, size, |rng| digit(rng, 1..=9))
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: u64, width: usize) {
+    bits.extend((0..width).rev().map(|i| (value >> i) & 1 == 1));
+}
+
+fn literal_packet(rng: &mut Rng, value: u64, bits: &mut Vec<bool>) {
+    push_bits(bits, rng.below(8) as u64, 3);
+    push_bits(bits, 4, 3);
+    let groups = (64 - value.leading_zeros() as usize).max(1).div_ceil(4);
+    for group in (0..groups).rev() {
+        push_bits(bits, (group > 0) as u64, 1);
+        push_bits(bits, (value >> (group * 4)) & 0xf, 4);
+    }
+}
+
+/// Append a random packet, spending one of `budget` on it and the rest on
+/// its sub-packets. Products only ever multiply a few small literals, so
+/// that evaluating the transmission can't overflow.
+fn packet(rng: &mut Rng, budget: &mut usize, depth: usize, bits: &mut Vec<bool>) {
+    *budget = budget.saturating_sub(1);
+    if *budget == 0 || depth >= 6 || rng.chance(1, 4) {
+        let value = rng.range(0..=(1 << 20)) as u64;
+        return literal_packet(rng, value, bits);
+    }
+
+    let type_id = *rng.choose(&[0, 1, 2, 3, 5, 6, 7]);
+    let mut payload: Vec<bool> = Vec::new();
+    let children = match type_id {
+        1 => {
+            let children = rng.range(1..=3) as usize;
+            for _ in 0..children {
+                let value = rng.range(0..=255) as u64;
+                literal_packet(rng, value, &mut payload);
+            }
+            children
+        }
+        5..=7 => 2,
+        _ => rng.range(1..=4) as usize,
+    };
+    if type_id != 1 {
+        for _ in 0..children {
+            packet(rng, budget, depth + 1, &mut payload);
+        }
+    }
+
+    push_bits(bits, rng.below(8) as u64, 3);
+    push_bits(bits, type_id, 3);
+    if payload.len() >= 1 << 15 || rng.chance(1, 2) {
+        push_bits(bits, 1, 1);
+        push_bits(bits, children as u64, 11);
+    } else {
+        push_bits(bits, 0, 1);
+        push_bits(bits, payload.len() as u64, 15);
+    }
+    bits.extend(payload);
+}
+
+/// A hexadecimal BITS transmission of about `size` packets
+pub fn day16(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut bits = Vec::with_capacity(size * 32);
+    let mut budget = size.max(1);
+    packet(&mut rng, &mut budget, 0, &mut bits);
+    while bits.len() % 8 != 0 {
+        bits.push(false);
+    }
+    let hex: String = bits
+        .chunks(4)
+        .map(|nibble| {
+            let value = nibble.iter().fold(0, |acc, bit| (acc << 1) | *bit as u32);
+            std::char::from_digit(value, 16)
+                .unwrap()
+                .to_ascii_uppercase()
+        })
+        .collect();
+    lines(vec![hex])
+}
+
+/// A target area ahead of and below the probe, growing further away with
+/// `size`. Sizes above 9 are treated as 9, to stay within the solver's
+/// search.
+pub fn day17(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let scale = size.clamp(1, 9) as i64;
+    let x_lower = rng.range(10..=10 + 20 * scale);
+    let x_upper = x_lower + rng.range(5..=5 + 5 * scale);
+    let y_upper = -rng.range(5..=5 + 10 * scale);
+    let y_lower = y_upper - rng.range(5..=5 + 5 * scale);
+    lines(vec![format!(
+        "target area: x={}..{}, y={}..{}",
+        x_lower, x_upper, y_lower, y_upper
+    )])
+}
+
+fn snailfish(rng: &mut Rng, depth: usize) -> String {
+    if depth == 4 || (depth > 0 && rng.chance(2, 5)) {
+        digit(rng, 0..=9).to_string()
+    } else {
+        format!(
+            "[{},{}]",
+            snailfish(rng, depth + 1),
+            snailfish(rng, depth + 1)
+        )
+    }
+}
+
+/// `size` reduced snailfish numbers
+pub fn day18(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|_| snailfish(&mut rng, 0)))
+}
+
+/// Reports from `size` scanners, each overlapping one of the scanners
+/// before it by at least twelve beacons
+pub fn day19(seed: u64, size: usize) -> String {
+    const RANGE: i64 = 1000;
+    const OVERLAP: usize = 12;
+    let mut rng = Rng::new(seed);
+    let rotations = day19::rotations();
+
+    let mut scanners: Vec<Point> = vec![[0, 0, 0]];
+    let mut seen: HashSet<Point> = HashSet::new();
+    let mut beacons: Vec<Point> = Vec::new();
+    let mut add_beacon = |rng: &mut Rng, lower: [i64; 3], upper: [i64; 3]| {
+        let mut beacon = [0; 3];
+        for axis in 0..3 {
+            beacon[axis] = rng.range(lower[axis]..=upper[axis]) as i32;
+        }
+        if seen.insert(beacon) {
+            beacons.push(beacon);
+        }
+    };
+    let bounds = |p: &Point| (p.map(|a| a as i64 - RANGE), p.map(|a| a as i64 + RANGE));
+
+    for _ in 1..size.max(1) {
+        let parent = *rng.choose(&scanners);
+        let scanner = parent.map(|a| a + rng.range(-1100..=1100) as i32);
+        let ((p_lower, p_upper), (s_lower, s_upper)) = (bounds(&parent), bounds(&scanner));
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        for axis in 0..3 {
+            lower[axis] = p_lower[axis].max(s_lower[axis]);
+            upper[axis] = p_upper[axis].min(s_upper[axis]);
+        }
+        for _ in 0..OVERLAP {
+            add_beacon(&mut rng, lower, upper);
+        }
+        scanners.push(scanner);
+    }
+    for scanner in &scanners {
+        let (lower, upper) = bounds(scanner);
+        for _ in 0..14 {
+            add_beacon(&mut rng, lower, upper);
+        }
+    }
+
+    let mut out = Vec::new();
+    for (id, scanner) in scanners.iter().enumerate() {
+        if id > 0 {
+            out.push(String::new());
+        }
+        out.push(format!("--- scanner {} ---", id));
+        let rotation = rng.choose(&rotations);
+        let mut visible: Vec<Point> = beacons
+            .iter()
+            .map(|b| [b[0] - scanner[0], b[1] - scanner[1], b[2] - scanner[2]])
+            .filter(|b| b.iter().all(|a| (*a as i64).abs() <= RANGE))
+            .map(|b| day19::rotate(rotation, &b))
+            .collect();
+        rng.shuffle(&mut visible);
+        out.extend(
+            visible
+                .iter()
+                .map(|b| format!("{},{},{}", b[0], b[1], b[2])),
+        );
+    }
+    lines(out)
+}
+
+/// An image enhancement algorithm and a `size` by `size` image. The
+/// algorithm never leaves infinitely many pixels lit.
+pub fn day20(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut algorithm: Vec<char> = (0..512)
+        .map(|_| if rng.chance(1, 2) { '#' } else { '.' })
+        .collect();
+    // A lit background must go dark again on the next step
+    if algorithm[0] == '#' {
+        algorithm[511] = '.';
+    }
+    let image = grid(&mut rng, size, size, |rng| {
+        if rng.chance(1, 2) {
+            '#'
+        } else {
+            '.'
+        }
+    });
+    format!("{}\n\n{}", algorithm.into_iter().collect::<String>(), image)
+}
+
+/// Both players' starting positions
+pub fn day21(seed: u64, _size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    lines(
+        (1..=2).map(|player| format!("Player {} starting position: {}", player, rng.range(1..=10))),
+    )
+}
+
+/// `size` reboot steps, the first half of them inside the initialization
+/// region
+pub fn day22(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    lines((0..size).map(|i| {
+        let on = i == 0 || rng.chance(2, 3);
+        let ranges: Vec<String> = ["x", "y", "z"]
+            .iter()
+            .map(|axis| {
+                let (lower, upper) = if i < size / 2 + 1 {
+                    let lower = rng.range(-50..=40);
+                    (lower, (lower + rng.range(0..=30)).min(50))
+                } else {
+                    let lower = rng.range(-100_000..=90_000);
+                    (lower, lower + rng.range(1000..=40_000))
+                };
+                format!("{}={}..{}", axis, lower, upper)
+            })
+            .collect();
+        format!("{} {}", if on { "on" } else { "off" }, ranges.join(","))
+    }))
+}
+
+/// Eight amphipods in the four rooms, mixed up by `size` random swaps of
+/// two of them
+pub fn day23(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut amphipods = ['A', 'B', 'C', 'D', 'A', 'B', 'C', 'D'];
+    for _ in 0..size {
+        let (a, b) = (rng.below(8), rng.below(8));
+        amphipods.swap(a, b);
+    }
+    let row = |row: &[char]| joined(row, "#");
+    lines(vec![
+        "#############".to_string(),
+        "#...........#".to_string(),
+        format!("###{}###", row(&amphipods[..4])),
+        format!("  #{}#", row(&amphipods[4..])),
+        "  #########".to_string(),
+    ])
+}
+
+/// A MONAD program that accepts at least one model number
+pub fn day24(seed: u64, _size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut params = Vec::with_capacity(14);
+    let mut stack = Vec::with_capacity(7);
+    let mut pushes_left = 7;
+    for _ in 0..14 {
+        let push_offset = rng.range(1..=16);
+        if stack.is_empty() || (pushes_left > 0 && rng.chance(1, 2)) {
+            pushes_left -= 1;
+            stack.push(push_offset);
+            params.push((1, rng.range(10..=16), push_offset));
+        } else {
+            let diff = rng.range(-8..=8);
+            let pushed = stack.pop().unwrap();
+            params.push((26, diff - pushed, push_offset));
+        }
+    }
+    day24::monad_source(&params) + "\n"
+}
+
+/// A `size` by `size` sea floor of sea cucumbers
+pub fn day25(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    grid(&mut rng, size, size, |rng| *rng.choose(&['>', 'v', '.']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle;
+
+    fn size(day: u8) -> usize {
+        match day {
+            12 => 4,
+            17 => 2,
+            19 => 3,
+            23 => 2,
+            _ => 8,
+        }
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(7);
+        let first: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        let mut rng = Rng::new(7);
+        assert_eq!((0..4).map(|_| rng.next_u64()).collect::<Vec<u64>>(), first);
+        assert_ne!(Rng::new(8).next_u64(), first[0]);
+
+        assert!((0..100).all(|_| (-3..=3).contains(&rng.range(-3..=3))));
+        assert!((0..100).all(|_| rng.below(5) < 5));
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<u32>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_deterministic() {
+        for day in 1..=25 {
+            let input = generate(day, 1, size(day)).unwrap();
+            assert_eq!(generate(day, 1, size(day)), Some(input.clone()));
+            if day != 21 {
+                assert_ne!(generate(day, 2, size(day)), Some(input), "Day {}", day);
+            }
+        }
+        assert_eq!(generate(0, 1, 1), None);
+        assert_eq!(generate(26, 1, 1), None);
+    }
+
+    #[test]
+    fn test_parses() {
+        for day in 1..=25 {
+            let solver = puzzle::solver(day).unwrap();
+            for seed in 0..5 {
+                let input = generate(day, seed, size(day)).unwrap();
+                if let Err(e) = solver.parse(&mut input.as_bytes()) {
+                    panic!("Day {} seed {}: {:#}\n{}", day, seed, e, input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solvable() {
+        // Day 11's second star and day 25 only finish on well-behaved
+        // inputs
+        for day in 1..=24 {
+            let solver = puzzle::solver(day).unwrap();
+            for seed in 0..3 {
+                let input = generate(day, seed, size(day)).unwrap();
+                let parsed = solver.parse(&mut input.as_bytes()).unwrap();
+                let mut answers = vec![solver.star1(parsed.as_ref())];
+                if day != 11 {
+                    answers.push(solver.star2(parsed.as_ref()));
+                }
+                for answer in answers {
+                    if let Err(e) = answer {
+                        panic!("Day {} seed {}: {:#}\n{}", day, seed, e, input);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        // Day 17's target stops growing once it's as far as can be solved
+        assert_eq!(day17(3, 9), day17(3, 100));
+        assert_ne!(day17(3, 8), day17(3, 9));
+
+        // An organized burrow takes no energy at all
+        let burrow = crate::day23::parse_str(&day23(3, 0)).unwrap();
+        assert_eq!(crate::day23::star1(&burrow).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_day13_letters() {
+        let input = day13(5, 6);
+        let manual = crate::day13::parse_str(&input).unwrap();
+        let paper = crate::day13::star2((manual.0, &manual.1));
+        assert_eq!(ocr::recognize(&paper).unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,338 @@
+//! A rectangular 2-D grid, for the puzzles laid out on a map of cells.
+//!
+//! Cells are addressed by `(x, y)`, with the origin at the top left, and are
+//! stored flat in row-major order.
+
+use std::fmt;
+use std::ops::{Index, IndexMut, RangeInclusive};
+
+use crate::parser::{LineParser, ParseError};
+
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid with every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// A grid with each cell set to `f(x, y)`
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// A grid from a list of rows, provided they are all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.cells[(y * self.width) + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[(y * self.width) + x])
+        } else {
+            None
+        }
+    }
+
+    /// Every `(x, y)` in the grid, in row-major order
+    pub fn points(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell along with its `(x, y)`, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// Every cell, mutably, in row-major order
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[(y * self.width)..((y + 1) * self.width)]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks() refuses a zero size, but then there are no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} is outside of the grid", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// The orthogonally adjacent points to `(x, y)` that are inside the grid
+    pub fn neighbors4(&self, x: usize, y: usize) -> Neighbors {
+        Neighbors::new(&OFFSETS_4, (x, y), (self.width, self.height))
+    }
+
+    /// The orthogonally and diagonally adjacent points to `(x, y)` that are
+    /// inside the grid
+    pub fn neighbors8(&self, x: usize, y: usize) -> Neighbors {
+        Neighbors::new(&OFFSETS_8, (x, y), (self.width, self.height))
+    }
+}
+
+impl Grid<u8> {
+    /// A grid of single decimal digits, one row per line, where every digit
+    /// must be in `valid`. `expected` describes a valid digit for errors.
+    pub fn parse_digits<I: Iterator<Item = String>>(
+        data: I,
+        expected: &str,
+        valid: RangeInclusive<u8>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut cells = Vec::new();
+        let mut height = 0;
+        for (i, line) in data.enumerate() {
+            let mut parser = LineParser::new(i + 1, &line);
+            let start = cells.len();
+            while width.map_or(!parser.is_empty(), |w| cells.len() - start < w) {
+                let digit = parser.char_matching(expected, |c| {
+                    c.is_ascii_digit() && valid.contains(&(c as u8 - b'0'))
+                })?;
+                cells.push(digit as u8 - b'0');
+            }
+            if cells.len() == start {
+                return Err(parser.error(expected));
+            }
+            parser.end()?;
+            width = Some(cells.len() - start);
+            height += 1;
+        }
+        match width {
+            Some(width) => Ok(Grid {
+                width,
+                height,
+                cells,
+            }),
+            None => Err(ParseError::end_of_input(1, "a row of digits")),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "({}, {}) is outside of a {}x{} grid",
+                x, y, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside of a {}x{} grid", x, y, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the points adjacent to a cell. It doesn't borrow the grid,
+/// so the grid can be updated while visiting the neighbors.
+#[derive(Debug, Clone)]
+pub struct Neighbors {
+    offsets: std::slice::Iter<'static, (isize, isize)>,
+    center: (usize, usize),
+    size: (usize, usize),
+}
+
+impl Neighbors {
+    fn new(
+        offsets: &'static [(isize, isize)],
+        center: (usize, usize),
+        size: (usize, usize),
+    ) -> Self {
+        Neighbors {
+            offsets: offsets.iter(),
+            center,
+            size,
+        }
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.center;
+        let (width, height) = self.size;
+        self.offsets.find_map(|&(dx, dy)| {
+            let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+            let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+            Some((nx, ny))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_grid() -> Grid<u8> {
+        Grid::parse_digits(
+            ["123", "456"].iter().map(|s| s.to_string()),
+            "a digit",
+            0..=9,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_digits() {
+        let grid = sample_grid();
+        assert_eq!((grid.width(), grid.height(), grid.len()), (3, 2, 6));
+        assert_eq!(grid.to_string(), "123\n456");
+
+        let parse = |s: &str| Grid::parse_digits(s.lines().map(String::from), "a digit", 1..=9);
+        let err = parse("").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "a row of digits"));
+        let err = parse("123\n45\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        let err = parse("123\n4567\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        let err = parse("123\n405\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert!(parse("12x\n").is_err());
+        assert!(parse("\n").is_err());
+    }
+
+    #[test]
+    fn test_access() {
+        let mut grid = sample_grid();
+        assert_eq!(grid.get(2, 1), Some(&6));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        grid[(1, 0)] = 9;
+        *grid.get_mut(0, 1).unwrap() += 1;
+        assert_eq!(grid.to_string(), "193\n556");
+        assert_eq!(grid.row(1), &[5, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<u8>>(), vec![3, 6]);
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+
+        let from_fn = Grid::from_fn(3, 2, |x, y| (y * 3 + x + 1) as u8);
+        assert_eq!(from_fn, sample_grid());
+        assert_eq!(
+            Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            Some(sample_grid())
+        );
+        assert_eq!(Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5]]), None);
+        assert!(Grid::<u8>::new(0, 0, 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let grid = sample_grid();
+        let _ = grid[(3, 0)];
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, 0u8);
+        let corner: Vec<(usize, usize)> = grid.neighbors4(0, 0).collect();
+        assert_eq!(corner, vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors4(1, 1).count(), 4);
+        assert_eq!(grid.neighbors8(1, 1).count(), 8);
+        let edge: Vec<(usize, usize)> = grid.neighbors8(2, 1).collect();
+        assert_eq!(edge, vec![(1, 0), (2, 0), (1, 1), (1, 2), (2, 2)]);
+        assert_eq!(Grid::new(1, 1, 0u8).neighbors8(0, 0).count(), 0);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod answer;
+pub mod bignum;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -8,7 +10,14 @@ pub mod day15;
 pub mod day16;
 pub mod day17;
 pub mod day18;
+pub mod day19;
 pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
 pub mod day3;
 pub mod day4;
 pub mod day5;
@@ -16,3 +25,13 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+#[cfg(test)]
+mod differential;
+pub mod generate;
+pub mod grid;
+pub mod ocr;
+pub mod parser;
+pub mod puzzle;
+pub mod report;
+pub mod trace;
+pub mod verify;
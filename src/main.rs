@@ -1,170 +1,510 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use aoc2021::answer::Answer;
+use aoc2021::puzzle::{self, Solver};
+use aoc2021::report::{Format, Record, Report, Step};
+use aoc2021::trace::{self, Level};
+use aoc2021::verify::{Expected, Verdict};
+
+const USAGE: &str = "\
+Usage: aoc2021 [run|verify] [OPTIONS]
+
+Commands:
+  run                   Solve the puzzles and print their answers (the default)
+  verify                Solve the puzzles and check every answer against the
+                        expected answers, failing on any mismatch. Days
+                        without an input are reported as missing
+
+Options:
+  -d, --day <N>         Run only day N
+  -a, --all             Run every implemented day (the default)
+  -s, --star <1|2>      Run only the given star
+  -i, --input <PATH>    Read the puzzle input from PATH, or from stdin if PATH
+                        is '-'. Requires --day
+      --data-dir <DIR>  Directory holding the N.txt puzzle inputs [default: data]
+  -f, --format <FMT>    How to report answers and timings: 'table' prints
+                        answers as they are found then a summary table,
+                        'json' and 'csv' print one record per step of every
+                        day [default: table]
+      --answers <PATH>  Expected answers to verify against
+                        [default: <data-dir>/answers.txt]
+  -t, --trace <N[:LVL]> Print what day N's solver is doing to stderr, at
+                        level 'info', 'debug' or 'trace' [default: trace].
+                        May be repeated
+  -h, --help            Print this message";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Input {
+    Stdin,
+    File(PathBuf),
+}
 
-use aoc2021::{
-    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day2, day3, day4, day5,
-    day6, day7, day8, day9,
-};
+impl Input {
+    fn open(&self) -> Result<Box<dyn BufRead>> {
+        match self {
+            Input::Stdin => Ok(Box::new(std::io::stdin().lock())),
+            Input::File(path) => {
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("Unable to open {}", path.display()))?;
+                Ok(Box::new(std::io::BufReader::new(file)))
+            }
+        }
+    }
+}
 
-fn main() -> Result<()> {
-    let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    day: Option<u8>,
+    star: Option<u8>,
+    input: Option<Input>,
+    data_dir: PathBuf,
+    format: Format,
+    answers: Option<PathBuf>,
+    trace: Vec<(u8, Level)>,
+}
 
-    // Day 1
-    let day1_data = day1::data(&data_dir)?;
-    let star1_count = day1::star1(&day1_data);
-    println!("[Star 1] Count of increasing depths: {}", star1_count);
-    let star2_count = day1::star2(&day1_data);
-    println!(
-        "[Star 2] Count of increasing depths of window size 3: {}",
-        star2_count
-    );
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            day: None,
+            star: None,
+            input: None,
+            data_dir: PathBuf::from("data"),
+            format: Format::Table,
+            answers: None,
+            trace: Vec::new(),
+        }
+    }
+}
 
-    // Day 2
-    let day2_data = day2::data(&data_dir)?;
-    let star1_count = day2::star1(&day2_data);
-    println!("[Star 1] Travel distance: {}", star1_count);
-    let star2_count = day2::star2(&day2_data);
-    println!("[Star 2] Travel distance: {}", star2_count);
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Run(Options),
+    Verify(Options),
+    Help,
+}
 
-    // Day 3
-    let mut day3_data = day3::data(&data_dir)?;
-    let star1_count = day3::star1(&day3_data);
-    println!("[Star 1] Power consumption: {}", star1_count);
+impl Command {
+    fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self> {
+        let mut args = args.iter().map(AsRef::as_ref).peekable();
+        let verify = match args.peek() {
+            Some(&"run") => {
+                args.next();
+                false
+            }
+            Some(&"verify") => {
+                args.next();
+                true
+            }
+            _ => false,
+        };
+
+        let mut options = Options::default();
+        let mut all = false;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))
+            };
+            match arg {
+                "-h" | "--help" => return Ok(Command::Help),
+                "-a" | "--all" => all = true,
+                "-d" | "--day" => {
+                    let day = value()?;
+                    options.day = Some(
+                        day.parse()
+                            .with_context(|| format!("Invalid day: {}", day))?,
+                    );
+                }
+                "-s" | "--star" => {
+                    options.star = match value()? {
+                        "1" => Some(1),
+                        "2" => Some(2),
+                        star => bail!("Invalid star: {}", star),
+                    };
+                }
+                "-i" | "--input" => {
+                    options.input = match value()? {
+                        "-" => Some(Input::Stdin),
+                        path => Some(Input::File(PathBuf::from(path))),
+                    };
+                }
+                "--data-dir" => options.data_dir = PathBuf::from(value()?),
+                "-f" | "--format" => options.format = value()?.parse()?,
+                "--answers" => options.answers = Some(PathBuf::from(value()?)),
+                "-t" | "--trace" => options.trace.push(parse_trace(value()?)?),
+                other => bail!("Unrecognized argument: {}", other),
+            }
+        }
+
+        if all && options.day.is_some() {
+            bail!("--all and --day are mutually exclusive");
+        }
+        if options.input.is_some() && options.day.is_none() {
+            bail!("--input requires --day");
+        }
+        if verify {
+            Ok(Command::Verify(options))
+        } else {
+            Ok(Command::Run(options))
+        }
+    }
+
+    /// Carry out a run or verify command, returning whether it succeeded
+    fn execute(&self) -> Result<bool> {
+        match self {
+            Command::Run(options) => {
+                enable_traces(options);
+                run(options)
+            }
+            Command::Verify(options) => {
+                enable_traces(options);
+                verify(options)
+            }
+            Command::Help => Ok(true),
+        }
+    }
+}
 
-    let star2_count = day3::star2(&mut day3_data);
-    println!("[Star 2] Life support: {}", star2_count);
+/// Parse a `--trace` value: an implemented day, optionally followed by a
+/// colon and a level
+fn parse_trace(value: &str) -> Result<(u8, Level)> {
+    let (day, level) = match value.split_once(':') {
+        Some((day, level)) => (day, level.parse()?),
+        None => (value, Level::Trace),
+    };
+    let day = day
+        .parse()
+        .with_context(|| format!("Invalid day: {}", day))?;
+    if puzzle::solver(day).is_none() {
+        bail!("Day {} is not implemented", day);
+    }
+    Ok((day, level))
+}
 
-    // Day 4
-    let (called, mut boards) = day4::data(&data_dir)?;
-    let star1_count = day4::star1(&called, &mut boards);
-    println!("[Star 1] Bingo high score: {}", star1_count);
+fn enable_traces(options: &Options) {
+    for (day, level) in &options.trace {
+        trace::enable(*day, *level);
+    }
+}
 
-    let star2_count = day4::star2(&called, &mut boards);
-    println!("[Star 2] Bingo low score: {}", star2_count);
+/// Where a day's input is when it isn't given explicitly
+fn data_path(options: &Options, day: u8) -> PathBuf {
+    options.data_dir.join(format!("{}.txt", day))
+}
 
-    // Day 5
-    let ranges = day5::data(&data_dir)?;
-    let star1_count = day5::star1(&ranges);
-    println!(
-        "[Star 1] Points with normal overlapping ranges: {}",
-        star1_count
-    );
+/// Parse and solve a single day, timing each step into `report` and
+/// reporting any failures to stderr. When running every day, days without
+/// an input file are skipped. Answers are printed as they are found
+/// if `echo` is set. Returns whether every requested step succeeded.
+fn run_day(solver: &dyn Solver, options: &Options, report: &mut Report, echo: bool) -> bool {
+    let day = solver.day();
+    let input = match &options.input {
+        Some(input) => input.clone(),
+        None => {
+            let path = data_path(options, day);
+            // Not every implemented day has an input to go with it
+            if options.day.is_none() && !path.exists() {
+                eprintln!("[Day {}] Skipped: no input at {}", day, path.display());
+                return true;
+            }
+            Input::File(path)
+        }
+    };
+
+    let mut reader = match input.open() {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("[Day {}] Failed to open input: {:#}", day, e);
+            report.push(Record {
+                day,
+                step: Step::Parse,
+                duration: Default::default(),
+                answer: None,
+                error: Some(format!("{:#}", e)),
+            });
+            return false;
+        }
+    };
+
+    let start = Instant::now();
+    let parsed = solver.parse(&mut reader);
+    let mut record = Record {
+        day,
+        step: Step::Parse,
+        duration: start.elapsed(),
+        answer: None,
+        error: None,
+    };
+    let parsed = match parsed {
+        Ok(parsed) => {
+            report.push(record);
+            parsed
+        }
+        Err(e) => {
+            eprintln!("[Day {}] Failed to parse input: {:#}", day, e);
+            record.error = Some(format!("{:#}", e));
+            report.push(record);
+            return false;
+        }
+    };
+
+    let mut ok = true;
+    for star in [1, 2] {
+        if matches!(options.star, Some(s) if s != star) {
+            continue;
+        }
+        let start = Instant::now();
+        let answer = if star == 1 {
+            solver.star1(parsed.as_ref())
+        } else {
+            solver.star2(parsed.as_ref())
+        };
+        let mut record = Record {
+            day,
+            step: Step::star(star),
+            duration: start.elapsed(),
+            answer: None,
+            error: None,
+        };
+        match answer {
+            Ok(answer) => {
+                if echo {
+                    print_answer(day, star, &answer);
+                }
+                record.answer = Some(answer);
+            }
+            Err(e) => {
+                eprintln!("[Day {} Star {}] Failed: {:#}", day, star, e);
+                record.error = Some(format!("{:#}", e));
+                ok = false;
+            }
+        }
+        report.push(record);
+    }
+    ok
+}
+
+fn print_answer(day: u8, star: u8, answer: &Answer) {
+    match answer {
+        // Bitmaps span several lines, so start them on a line of their own
+        Answer::Bitmap(_) => println!("[Day {} Star {}]\n{}", day, star, answer),
+        _ => println!("[Day {} Star {}] {}", day, star, answer),
+    }
+}
+
+/// The solvers selected by `options`
+fn solvers(options: &Options) -> Result<Vec<&'static dyn Solver>> {
+    match options.day {
+        Some(day) => {
+            Ok(vec![puzzle::solver(day).ok_or_else(|| {
+                anyhow!("Day {} is not implemented", day)
+            })?])
+        }
+        None => Ok(puzzle::registry().to_vec()),
+    }
+}
 
-    let star2_count = day5::star2(&ranges);
+fn run(options: &Options) -> Result<bool> {
+    let mut ok = true;
+    let mut report = Report::new();
+    for solver in solvers(options)? {
+        ok &= run_day(
+            solver,
+            options,
+            &mut report,
+            options.format == Format::Table,
+        );
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    if options.format == Format::Table {
+        writeln!(out)?;
+    }
+    report.write(options.format, &mut out)?;
+    Ok(ok)
+}
+
+fn verify(options: &Options) -> Result<bool> {
+    let expected = match &options.answers {
+        Some(path) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Unable to open {}", path.display()))?;
+            Expected::parse_reader(std::io::BufReader::new(file))?
+        }
+        None => Expected::data(&options.data_dir)?,
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for solver in solvers(options)? {
+        let day = solver.day();
+        // A day without an input can't be checked, which is worth knowing
+        let path = data_path(options, day);
+        if options.input.is_none() && !path.exists() {
+            for star in [1, 2] {
+                if matches!(options.star, Some(s) if s != star) {
+                    continue;
+                }
+                println!(
+                    "[Day {} Star {}] MISSING: no input at {}",
+                    day,
+                    star,
+                    path.display()
+                );
+                missing += 1;
+            }
+            continue;
+        }
+
+        let mut report = Report::new();
+        run_day(solver, options, &mut report, false);
+        for record in report.records() {
+            let star = match record.step {
+                Step::Parse if record.error.is_some() => {
+                    println!("[Day {}] FAIL: unable to parse input", record.day);
+                    failed += 1;
+                    continue;
+                }
+                Step::Parse => continue,
+                Step::Star1 => 1,
+                Step::Star2 => 2,
+            };
+            let verdict = match (&record.answer, &record.error) {
+                (Some(answer), None) => expected.check(record.day, star, answer),
+                _ => Verdict::Fail {
+                    expected: expected.get(record.day, star).unwrap_or("?").to_string(),
+                    actual: "an error".to_string(),
+                },
+            };
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Fail { .. } => failed += 1,
+                Verdict::Missing { .. } => missing += 1,
+            }
+            println!("[Day {} Star {}] {}", record.day, star, verdict);
+        }
+    }
     println!(
-        "[Star 1] Points with any overlapping ranges: {}",
-        star2_count
+        "\n{} passed, {} failed, {} missing",
+        passed, failed, missing
     );
+    Ok(failed == 0)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = match Command::parse(&args) {
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            0
+        }
+        Ok(command) => match command.execute() {
+            Ok(true) => 0,
+            Ok(false) => 1,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                1
+            }
+        },
+        Err(e) => {
+            eprintln!("Error: {:#}\n\n{}", e, USAGE);
+            2
+        }
+    };
+    std::process::exit(code);
+}
 
-    // Day 6
-    let pond = day6::data(&data_dir)?;
-    let star1_count = day6::star1(&pond);
-    println!("[Star 1] Fish in pond after 80 days: {}", star1_count);
-
-    let star2_count = day6::star2(&pond);
-    println!("[Star 1] Fish in pond after 256 days: {}", star2_count);
-
-    // Day 7
-    let data = day7::data(&data_dir)?;
-    let star1_count = day7::star1(&data);
-    println!("[Star 1] Fuel usage: {}", star1_count);
-
-    let star2_count = day7::star2(&data);
-    println!("[Star 2] Fuel usage: {}", star2_count);
-
-    // Day 8
-    let data = day8::data(&data_dir)?;
-    let star1_count = day8::star1(data.as_ref());
-    println!("[Star 1] Unique light patterns: {}", star1_count);
-
-    let star2_count = day8::star2(data.as_ref());
-    println!("[Star 2] Display sum: {}", star2_count);
-
-    // Day 9
-    let data = day9::data(&data_dir)?;
-    let data: Vec<&[u8]> = data.iter().map(|r| r.as_slice()).collect();
-    let star1_count = day9::star1(data.as_ref());
-    println!("[Star 1] Low point risk: {}", star1_count);
-
-    let star2_count = day9::star2(data.as_ref());
-    println!("[Star 2] Basin size product: {}", star2_count);
-
-    // Day 10
-    let data = day10::data(&data_dir)?;
-    let star1_count = day10::star1(data.as_ref());
-    println!("[Star 1] Parse error score: {}", star1_count);
-
-    let star2_count = day10::star2(data.as_ref());
-    println!("[Star 2] Parse completion score: {}", star2_count);
-
-    // Day 11
-    let data = day11::data(&data_dir)?;
-    let star1_count = day11::star1(data.clone());
-    println!("[Star 1] Flashes after 100 steps: {}", star1_count);
-
-    let star2_count = day11::star2(data);
-    println!("[Star 2] Synchronization time: {}", star2_count);
-
-    // Day 12
-    let data = day12::data(&data_dir)?;
-    let star1_count = day12::star1(&data);
-    println!("[Star 1] Maze paths: {}", star1_count);
-
-    let star2_count = day12::star2(&data);
-    println!("[Star 2] Maze paths with one revisit: {}", star2_count);
-
-    // Day 13
-    let data = day13::data(&data_dir);
-    let (points, folds) = day13::parse(data);
-    let star1_count = day13::star1((points.clone(), &folds));
-    println!("[Star 1] Points after one fold: {}", star1_count);
-
-    let star2_count = day13::star2((points, &folds));
-    println!("[Star 2] Points after all folds: {}", star2_count);
-
-    // Day 14
-    let data = day14::data(&data_dir);
-    let (polymer, insertions) = day14::parse(data);
-    let star1_count = day14::star1(&polymer, &insertions);
-    println!("[Star 1] Polymer score after 10 steps: {}", star1_count);
-
-    let star2_count = day14::star2(&polymer, &insertions);
-    println!("[Star 2] Polymer score after 40 steps: {}", star2_count);
-
-    // Day 15
-    let data = day15::data(&data_dir);
-    let maze = day15::parse(data);
-    let star1_count = day15::star1(&maze);
-    println!("[Star 1] Maze risk score: {}", star1_count);
-
-    let star2_count = day15::star2(&maze);
-    println!("[Star 2] Maze risk score: {}", star2_count);
-
-    // Day 16
-    let data = day16::data(&data_dir);
-    let message = day16::parse(data);
-    let star1_count = day16::star1(&message);
-    println!("[Star 1] Version sum: {}", star1_count);
-
-    let star2_count = day16::star2(&message);
-    println!("[Star 2] Calculation: {}", star2_count);
-
-    // Day 17
-    let data = day17::data(&data_dir);
-    let target = day17::parse(&data);
-    let star1_count = day17::star1(&target);
-    println!("[Star 1] Highest Y: {}", star1_count);
-
-    let star2_count = day17::star2(&target);
-    println!("[Star 2] Firing solutions: {}", star2_count);
-
-    // Day 18
-    let data = day18::data(&data_dir);
-    let sums = day18::parse(data);
-    let star1_count = day18::star1(&sums);
-    println!("[Star 1] Sum magnitude: {}", star1_count);
-
-    let star2_count = day18::star2(&sums);
-    println!("[Star 2] Max magnitude: {}", star2_count);
-
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defaults() {
+        let empty: [&str; 0] = [];
+        assert_eq!(
+            Command::parse(&empty).unwrap(),
+            Command::Run(Options::default())
+        );
+        assert_eq!(
+            Command::parse(&["run", "--all"]).unwrap(),
+            Command::Run(Options::default())
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let command = Command::parse(&["run", "--day", "15", "-s", "2", "--input", "-"]).unwrap();
+        assert_eq!(
+            command,
+            Command::Run(Options {
+                day: Some(15),
+                star: Some(2),
+                input: Some(Input::Stdin),
+                ..Options::default()
+            })
+        );
+
+        let command =
+            Command::parse(&["-d", "3", "-i", "in.txt", "--data-dir", "/tmp", "-f", "csv"])
+                .unwrap();
+        assert_eq!(
+            command,
+            Command::Run(Options {
+                day: Some(3),
+                input: Some(Input::File(PathBuf::from("in.txt"))),
+                data_dir: PathBuf::from("/tmp"),
+                format: Format::Csv,
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            Command::parse(&["verify"]).unwrap(),
+            Command::Verify(Options::default())
+        );
+        assert_eq!(
+            Command::parse(&["verify", "-d", "13", "--answers", "a.txt"]).unwrap(),
+            Command::Verify(Options {
+                day: Some(13),
+                answers: Some(PathBuf::from("a.txt")),
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Command::parse(&["--day"]).is_err());
+        assert!(Command::parse(&["--day", "x"]).is_err());
+        assert!(Command::parse(&["--star", "3"]).is_err());
+        assert!(Command::parse(&["--input", "-"]).is_err());
+        assert!(Command::parse(&["--all", "--day", "1"]).is_err());
+        assert!(Command::parse(&["bogus"]).is_err());
+        assert!(Command::parse(&["--format", "xml"]).is_err());
+        assert_eq!(Command::parse(&["run", "-h"]).unwrap(), Command::Help);
+        assert!(Command::parse(&["verify", "--day"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        assert_eq!(
+            Command::parse(&["-d", "17", "--trace", "17", "-t", "16:info"]).unwrap(),
+            Command::Run(Options {
+                day: Some(17),
+                trace: vec![(17, Level::Trace), (16, Level::Info)],
+                ..Options::default()
+            })
+        );
+        assert!(Command::parse(&["--trace"]).is_err());
+        assert!(Command::parse(&["--trace", "18:loud"]).is_err());
+        assert!(Command::parse(&["--trace", "26"]).is_err());
+        assert!(Command::parse(&["--trace", ":debug"]).is_err());
+    }
 }
@@ -0,0 +1,224 @@
+//! Recognition of the capital letters Advent of Code draws with lit pixels,
+//! in its 4x6 font with a blank column between letters.
+//!
+//! 'Y' is five pixels wide, running into the blank column, so it isn't
+//! recognized.
+
+use std::fmt;
+
+use crate::answer::Bitmap;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+/// Glyph width, plus the blank column separating it from the next glyph
+const GLYPH_PITCH: usize = GLYPH_WIDTH + 1;
+
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// A glyph that didn't match any letter in the font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnrecognizedGlyph {
+    /// Position of the glyph in the text, counting from 0
+    pub index: usize,
+    /// The glyph's pixels, including its separating column if anything is
+    /// lit there
+    pub glyph: Bitmap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Letters are always exactly `GLYPH_HEIGHT` pixels tall
+    WrongHeight(usize),
+    Unrecognized {
+        /// The recognized text, with a '?' in place of each unrecognized glyph
+        partial: String,
+        glyphs: Vec<UnrecognizedGlyph>,
+    },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::WrongHeight(height) => write!(
+                f,
+                "Text must be {} pixels tall, not {}",
+                GLYPH_HEIGHT, height
+            ),
+            OcrError::Unrecognized { partial, glyphs } => {
+                write!(f, "Unrecognized glyphs in {:?}", partial)?;
+                for glyph in glyphs {
+                    write!(f, "\nGlyph {}:\n{}", glyph.index, glyph.glyph)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+fn matches(glyph: &Bitmap, pattern: &[&str; GLYPH_HEIGHT]) -> bool {
+    pattern.iter().enumerate().all(|(y, row)| {
+        row.bytes()
+            .enumerate()
+            .all(|(x, pixel)| glyph.get(x, y) == (pixel == b'#'))
+    })
+}
+
+/// Read the letters spelled out by the lit pixels in `bitmap`
+pub fn recognize(bitmap: &Bitmap) -> Result<String, OcrError> {
+    if bitmap.height() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(bitmap.height()));
+    }
+
+    let mut text = String::with_capacity(bitmap.width() / GLYPH_PITCH + 1);
+    let mut unrecognized = Vec::new();
+    for (index, x) in (0..bitmap.width()).step_by(GLYPH_PITCH).enumerate() {
+        let glyph = bitmap.crop(x, 0, GLYPH_WIDTH, GLYPH_HEIGHT);
+        let spacer = bitmap.crop(x + GLYPH_WIDTH, 0, 1, GLYPH_HEIGHT);
+        let letter = FONT
+            .iter()
+            .find(|(_, pattern)| matches(&glyph, pattern))
+            .map(|(letter, _)| *letter);
+        match letter {
+            Some(letter) if spacer.count() == 0 => text.push(letter),
+            _ => {
+                text.push('?');
+                let glyph = if spacer.count() == 0 {
+                    glyph
+                } else {
+                    bitmap.crop(x, 0, GLYPH_PITCH, GLYPH_HEIGHT)
+                };
+                unrecognized.push(UnrecognizedGlyph { index, glyph });
+            }
+        }
+    }
+
+    if unrecognized.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError::Unrecognized {
+            partial: text,
+            glyphs: unrecognized,
+        })
+    }
+}
+
+/// The letters the font can draw and recognize
+pub(crate) fn letters() -> impl Iterator<Item = char> {
+    FONT.iter().map(|(letter, _)| *letter)
+}
+
+/// Draw `text` in the font, the way a puzzle would, or `None` if it has a
+/// letter the font doesn't include
+pub(crate) fn render(text: &str) -> Option<Bitmap> {
+    let mut points = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let (_, pattern) = FONT.iter().find(|(letter, _)| *letter == c)?;
+        for (y, row) in pattern.iter().enumerate() {
+            for (x, pixel) in row.bytes().enumerate() {
+                if pixel == b'#' {
+                    points.push(((i * GLYPH_PITCH) + x, y));
+                }
+            }
+        }
+    }
+    Some(Bitmap::from_points(points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognize() {
+        for text in ["EPZGKCHU", "AHPRPAUZ", "ABCEFGHIJKLOPRSUZ", "L"] {
+            assert_eq!(recognize(&render(text).unwrap()), Ok(text.to_string()));
+        }
+        assert_eq!(letters().collect::<String>(), "ABCEFGHIJKLOPRSUZ");
+        assert_eq!(render("AQ"), None);
+    }
+
+    /// A bitmap drawn out row by row, with '#' for each lit pixel
+    fn drawn(rows: &[&str]) -> Bitmap {
+        let mut bitmap = Bitmap::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, pixel) in row.bytes().enumerate() {
+                bitmap.set(x, y, pixel == b'#');
+            }
+        }
+        bitmap
+    }
+
+    #[test]
+    fn test_recognize_drawn() {
+        let text = drawn(&[
+            "####...##.#..#.#...",
+            "#.......#.#..#.#...",
+            "###.....#.#..#.#...",
+            "#.......#.#..#.#...",
+            "#....#..#.#..#.#...",
+            "#.....##...##..####",
+        ]);
+        assert_eq!(recognize(&text), Ok("FJUL".to_string()));
+
+        // Y takes up the column that should separate it from the next letter
+        let y = drawn(&[
+            "#...#####",
+            ".#.#....#",
+            "..#....#.",
+            "..#...#..",
+            "..#..#...",
+            "..#..####",
+        ]);
+        match recognize(&y) {
+            Err(OcrError::Unrecognized { partial, .. }) => assert_eq!(partial, "?Z"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        let mut bitmap = render("EPZ").unwrap();
+        bitmap.set(GLYPH_PITCH + 2, 5, true);
+        match recognize(&bitmap) {
+            Err(OcrError::Unrecognized { partial, glyphs }) => {
+                assert_eq!(partial, "E?Z");
+                assert_eq!(glyphs.len(), 1);
+                assert_eq!(glyphs[0].index, 1);
+                assert_eq!(
+                    glyphs[0].glyph.to_string(),
+                    "###.\n#..#\n#..#\n###.\n#...\n#.#."
+                );
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let mut bitmap = render("EPZ").unwrap();
+        bitmap.set(GLYPH_WIDTH, 0, true);
+        let err = recognize(&bitmap).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Unrecognized glyphs in \"?PZ\""));
+
+        assert_eq!(recognize(&Bitmap::new(4, 5)), Err(OcrError::WrongHeight(5)));
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+
+/// What the parser ran into instead of the token it expected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Found {
+    Char(char),
+    EndOfLine,
+    EndOfInput,
+}
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::Char(c) => write!(f, "{:?}", c),
+            Found::EndOfLine => write!(f, "end of line"),
+            Found::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+/// A puzzle input parse failure, located by (1-based) line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: Found,
+}
+
+impl ParseError {
+    /// The input ran out before `line`, which was expected to hold `expected`
+    pub fn end_of_input<S: Into<String>>(line: usize, expected: S) -> Self {
+        ParseError {
+            line,
+            column: 1,
+            expected: expected.into(),
+            found: Found::EndOfInput,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A cursor over a single line of puzzle input, which keeps track of where
+/// it is so that failures can be reported as a `ParseError`.
+#[derive(Debug, Clone)]
+pub struct LineParser<'a> {
+    line: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> LineParser<'a> {
+    /// `line` is the 1-based line number of `text` in the puzzle input
+    pub fn new(line: usize, text: &'a str) -> Self {
+        LineParser {
+            line,
+            text: text.trim_end(),
+            pos: 0,
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    /// An error at the current position
+    pub fn error<S: Into<String>>(&self, expected: S) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column(),
+            expected: expected.into(),
+            found: self.peek().map(Found::Char).unwrap_or(Found::EndOfLine),
+        }
+    }
+
+    /// Consume `token`, or report the first character that doesn't match it
+    pub fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        for expected in token.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(format!("{:?}", token)));
+            }
+            self.pos += expected.len_utf8();
+        }
+        Ok(())
+    }
+
+    /// Consume `token` if the line continues with it
+    pub fn accept(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume a single character satisfying `pred`, described by `expected`
+    pub fn char_matching<F: Fn(char) -> bool>(
+        &mut self,
+        expected: &str,
+        pred: F,
+    ) -> Result<char, ParseError> {
+        match self.peek() {
+            Some(c) if pred(c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// Consume an optionally negative decimal integer, leaving the parser
+    /// where it was if there isn't one
+    pub fn integer<T: std::str::FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let sign_len = if rest.starts_with('-') { 1 } else { 0 };
+        let digit_len = rest[sign_len..]
+            .bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digit_len == 0 {
+            // Point past any sign at what should have been a digit
+            let mut after_sign = self.clone();
+            after_sign.pos += sign_len;
+            return Err(after_sign.error("a digit"));
+        }
+        let digits = &rest[..sign_len + digit_len];
+        match digits.parse::<T>() {
+            Ok(value) => {
+                self.pos = start + digits.len();
+                Ok(value)
+            }
+            Err(_) => Err(self.error(format!(
+                "an integer that fits in {}",
+                std::any::type_name::<T>()
+            ))),
+        }
+    }
+
+    /// Require that the whole line has been consumed
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expect() {
+        let mut parser = LineParser::new(3, "fold alng y=7");
+        let err = parser.expect("fold along ").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.column, 8);
+        assert_eq!(err.found, Found::Char('n'));
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 8: expected \"fold along \", found 'n'"
+        );
+    }
+
+    #[test]
+    fn test_integer() {
+        let mut parser = LineParser::new(1, "x=-12..300");
+        parser.expect("x=").unwrap();
+        assert_eq!(parser.integer::<isize>().unwrap(), -12);
+        parser.expect("..").unwrap();
+        let err = parser.integer::<u8>().unwrap_err();
+        assert_eq!(err.column, 8);
+        assert_eq!(parser.integer::<u16>().unwrap(), 300);
+        assert!(parser.end().is_ok());
+
+        let mut parser = LineParser::new(1, "-");
+        let err = parser.integer::<isize>().unwrap_err();
+        assert_eq!((err.column, err.found), (2, Found::EndOfLine));
+        assert_eq!(parser.column(), 1);
+
+        let mut parser = LineParser::new(1, "-x");
+        let err = parser.integer::<isize>().unwrap_err();
+        assert_eq!((err.column, err.found), (2, Found::Char('x')));
+        assert_eq!(parser.peek(), Some('-'));
+    }
+
+    #[test]
+    fn test_end() {
+        let mut parser = LineParser::new(1, "AB -> C  ");
+        parser.expect("AB -> ").unwrap();
+        assert!(parser.end().is_err());
+        assert_eq!(
+            parser.char_matching("a letter", |c| c.is_ascii_uppercase()),
+            Ok('C')
+        );
+        assert!(parser.end().is_ok());
+    }
+}
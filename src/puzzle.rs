@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use std::any::Any;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// A single day's puzzle: how to parse its input, and how to solve each star.
+pub trait Puzzle {
+    /// The day of the advent calendar this puzzle belongs to
+    const DAY: u8;
+
+    /// Parsed puzzle input, shared by both stars
+    type Input;
+
+    fn parse(input: &mut dyn BufRead) -> Result<Self::Input>;
+
+    fn star1(input: &Self::Input) -> Result<Answer>;
+
+    fn star2(input: &Self::Input) -> Result<Answer>;
+}
+
+/// Object-safe view of a `Puzzle`, so that every day can be driven the same
+/// way through the registry.
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Box<dyn Any>>;
+
+    fn star1(&self, input: &dyn Any) -> Result<Answer>;
+
+    fn star2(&self, input: &dyn Any) -> Result<Answer>;
+}
+
+impl<P> Solver for P
+where
+    P: Puzzle + Sync,
+    P::Input: 'static,
+{
+    fn day(&self) -> u8 {
+        P::DAY
+    }
+
+    fn parse(&self, input: &mut dyn BufRead) -> Result<Box<dyn Any>> {
+        P::parse(input).map(|parsed| Box::new(parsed) as Box<dyn Any>)
+    }
+
+    fn star1(&self, input: &dyn Any) -> Result<Answer> {
+        P::star1(downcast::<P>(input)?)
+    }
+
+    fn star2(&self, input: &dyn Any) -> Result<Answer> {
+        P::star2(downcast::<P>(input)?)
+    }
+}
+
+fn downcast<P: Puzzle>(input: &dyn Any) -> Result<&P::Input>
+where
+    P::Input: 'static,
+{
+    input
+        .downcast_ref::<P::Input>()
+        .ok_or_else(|| anyhow!("Input passed to day {} is not its parsed input", P::DAY))
+}
+
+static REGISTRY: [&dyn Solver; 25] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// Every implemented day, in calendar order
+pub fn registry() -> &'static [&'static dyn Solver] {
+    &REGISTRY
+}
+
+/// Look up the solver for a given day, if it has been implemented
+pub fn solver(day: u8) -> Option<&'static dyn Solver> {
+    REGISTRY.iter().copied().find(|s| s.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_order() {
+        for (i, solver) in registry().iter().enumerate() {
+            assert_eq!(solver.day() as usize, i + 1);
+        }
+        assert_eq!(solver(16).map(|s| s.day()), Some(16));
+        assert!(solver(0).is_none());
+    }
+
+    #[test]
+    fn test_solver_roundtrip() {
+        let solver = solver(1).unwrap();
+        let mut input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n".as_bytes();
+        let parsed = solver.parse(&mut input).unwrap();
+        assert_eq!(solver.star1(parsed.as_ref()).unwrap(), Answer::Integer(7));
+        assert_eq!(solver.star2(parsed.as_ref()).unwrap(), Answer::Integer(5));
+        assert!(solver.star1(&()).is_err());
+    }
+}
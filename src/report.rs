@@ -0,0 +1,280 @@
+//! Timings and answers collected by the runner, written out as a summary
+//! table or as JSON or CSV for tracking solver performance across commits.
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::answer::Answer;
+
+/// A timed step of solving a single day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Parse,
+    Star1,
+    Star2,
+}
+
+impl Step {
+    /// The step solving `star`, which must be 1 or 2
+    pub fn star(star: u8) -> Self {
+        match star {
+            1 => Step::Star1,
+            2 => Step::Star2,
+            _ => panic!("There is no star {}", star),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Parse => write!(f, "parse"),
+            Step::Star1 => write!(f, "star1"),
+            Step::Star2 => write!(f, "star2"),
+        }
+    }
+}
+
+/// The outcome of a single step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub step: Step,
+    pub duration: Duration,
+    /// Only stars have answers
+    pub answer: Option<Answer>,
+    /// Why the step failed, if it did
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => bail!("Unknown report format: {}", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    records: Vec<Record>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Report::default()
+    }
+
+    pub fn push(&mut self, record: Record) {
+        self.records.push(record);
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Time spent across every step
+    pub fn total(&self) -> Duration {
+        self.records.iter().map(|r| r.duration).sum()
+    }
+
+    pub fn write<W: Write>(&self, format: Format, out: &mut W) -> io::Result<()> {
+        match format {
+            Format::Table => self.write_table(out),
+            Format::Json => self.write_json(out),
+            Format::Csv => self.write_csv(out),
+        }
+    }
+
+    fn write_table<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>3}  {:<5}  {:>10}  Answer",
+            "Day", "Step", "Duration"
+        )?;
+        for record in &self.records {
+            let result = match (&record.answer, &record.error) {
+                (_, Some(error)) => format!("FAILED: {}", error),
+                // Bitmaps span several lines, which would break up the table
+                (Some(Answer::Bitmap(b)), None) => {
+                    format!("{}x{} bitmap", b.width(), b.height())
+                }
+                (Some(answer), None) => answer.to_string(),
+                (None, None) => String::new(),
+            };
+            let line = format!(
+                "{:>3}  {:<5}  {:>10}  {}",
+                record.day,
+                record.step,
+                format_duration(record.duration),
+                result
+            );
+            writeln!(out, "{}", line.trim_end())?;
+        }
+        writeln!(out, "Total: {}", format_duration(self.total()))
+    }
+
+    fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "[")?;
+        for (i, record) in self.records.iter().enumerate() {
+            let separator = if i + 1 < self.records.len() { "," } else { "" };
+            writeln!(
+                out,
+                "  {{\"day\": {}, \"step\": \"{}\", \"answer\": {}, \"duration_ns\": {}, \"error\": {}}}{}",
+                record.day,
+                record.step,
+                json_string(record.answer.as_ref().map(|a| a.to_string())),
+                record.duration.as_nanos(),
+                json_string(record.error.clone()),
+                separator
+            )?;
+        }
+        writeln!(out, "]")
+    }
+
+    fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "day,step,answer,duration_ns,error")?;
+        for record in &self.records {
+            writeln!(
+                out,
+                "{},{},{},{},{}",
+                record.day,
+                record.step,
+                csv_field(record.answer.as_ref().map(|a| a.to_string())),
+                record.duration.as_nanos(),
+                csv_field(record.error.clone())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1?}", duration)
+}
+
+fn json_string(s: Option<String>) -> String {
+    let s = match s {
+        Some(s) => s,
+        None => return "null".to_string(),
+    };
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn csv_field(s: Option<String>) -> String {
+    match s {
+        Some(s) if s.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        }
+        Some(s) => s,
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Bitmap;
+
+    fn sample_report() -> Report {
+        let mut report = Report::new();
+        report.push(Record {
+            day: 13,
+            step: Step::Parse,
+            duration: Duration::from_micros(250),
+            answer: None,
+            error: None,
+        });
+        report.push(Record {
+            day: 13,
+            step: Step::Star1,
+            duration: Duration::from_nanos(1500),
+            answer: Some(Answer::Integer(720)),
+            error: None,
+        });
+        report.push(Record {
+            day: 13,
+            step: Step::Star2,
+            duration: Duration::from_millis(2),
+            answer: Some(Answer::Bitmap(Bitmap::from_points(vec![(0, 0), (1, 1)]))),
+            error: Some("Unrecognized \"glyph\", oh no".to_string()),
+        });
+        report
+    }
+
+    fn render(report: &Report, format: Format) -> String {
+        let mut out = Vec::new();
+        report.write(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        let table = render(&sample_report(), Format::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], " 13  parse     250.0µs");
+        assert_eq!(lines[2], " 13  star1       1.5µs  720");
+        assert!(lines[3].ends_with("FAILED: Unrecognized \"glyph\", oh no"));
+        assert_eq!(lines[4], "Total: 2.3ms");
+    }
+
+    #[test]
+    fn test_json() {
+        let json = render(&sample_report(), Format::Json);
+        let lines: Vec<&str> = json.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[2],
+            "  {\"day\": 13, \"step\": \"star1\", \"answer\": \"720\", \"duration_ns\": 1500, \"error\": null},"
+        );
+        assert_eq!(
+            lines[3],
+            "  {\"day\": 13, \"step\": \"star2\", \"answer\": \"#.\\n.#\", \"duration_ns\": 2000000, \"error\": \"Unrecognized \\\"glyph\\\", oh no\"}"
+        );
+        assert_eq!(render(&Report::new(), Format::Json), "[\n]\n");
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = render(&sample_report(), Format::Csv);
+        assert_eq!(
+            csv,
+            "day,step,answer,duration_ns,error\n\
+             13,parse,,250000,\n\
+             13,star1,720,1500,\n\
+             13,star2,\"#.\n.#\",2000000,\"Unrecognized \"\"glyph\"\", oh no\"\n"
+        );
+        assert_eq!("csv".parse::<Format>().unwrap(), Format::Csv);
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Opt-in tracing of what the solvers are doing, in place of commented-out
+//! debugging prints.
+//!
+//! Solvers emit named events with fields through [`trace_event!`], such as
+//! a packet being parsed or a shot missing its target. Tracing is off by
+//! default; the runner turns it on for chosen days with `--trace`. Enabled
+//! events are printed to stderr, unless the current thread is collecting
+//! them with [`capture`].
+
+use anyhow::bail;
+use std::cell::RefCell;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// How much detail an event carries. Enabling a level enables the less
+/// detailed levels too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// A summary of a whole star
+    Info = 1,
+    /// A notable step along the way
+    Debug = 2,
+    /// Every step, however small
+    Trace = 3,
+}
+
+impl FromStr for Level {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            other => bail!("Unknown trace level: {}", other),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "INFO"),
+            Level::Debug => write!(f, "DEBUG"),
+            Level::Trace => write!(f, "TRACE"),
+        }
+    }
+}
+
+/// A single thing a solver did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub day: u8,
+    pub level: Level,
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Event {
+    /// The value of the field called `key`, if the event has one
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[Day {} {}] {}", self.day, self.level, self.name)?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+const DAYS: usize = 25;
+
+/// The most detailed level enabled for each day, or zero if it's off
+static LEVELS: [AtomicU8; DAYS + 1] = [const { AtomicU8::new(0) }; DAYS + 1];
+
+/// Whether tracing has ever been turned on, so that the solvers' hot loops
+/// only pay for a single load while it's off
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+struct Capture {
+    day: u8,
+    level: Level,
+    events: Vec<Event>,
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Capture>> = const { RefCell::new(None) };
+}
+
+/// Print `day`'s events up to `level` from now on. Days that don't exist
+/// are ignored.
+pub fn enable(day: u8, level: Level) {
+    if let Some(enabled) = LEVELS.get(day as usize) {
+        enabled.store(level as u8, Ordering::Relaxed);
+        ACTIVE.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn disable(day: u8) {
+    if let Some(enabled) = LEVELS.get(day as usize) {
+        enabled.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Whether an event for `day` at `level` would go anywhere
+pub fn enabled(day: u8, level: Level) -> bool {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return false;
+    }
+    let captured = CAPTURE
+        .with(|capture| matches!(&*capture.borrow(), Some(c) if c.day == day && level <= c.level));
+    captured
+        || LEVELS
+            .get(day as usize)
+            .is_some_and(|enabled| enabled.load(Ordering::Relaxed) >= level as u8)
+}
+
+/// Send an event on to this thread's capture or to stderr. Use
+/// [`trace_event!`] instead, which skips building the event when it would
+/// go nowhere.
+#[doc(hidden)]
+pub fn emit(
+    day: u8,
+    level: Level,
+    name: &'static str,
+    fields: &[(&'static str, &dyn fmt::Display)],
+) {
+    let event = Event {
+        day,
+        level,
+        name,
+        fields: fields
+            .iter()
+            .map(|(key, value)| (*key, value.to_string()))
+            .collect(),
+    };
+    let event = CAPTURE.with(|capture| match &mut *capture.borrow_mut() {
+        Some(c) if c.day == day && level <= c.level => {
+            c.events.push(event);
+            None
+        }
+        _ => Some(event),
+    });
+    if let Some(event) = event {
+        eprintln!("{}", event);
+    }
+}
+
+/// Run `f`, collecting `day`'s events up to `level` from this thread
+/// instead of printing them, and return them along with `f`'s result
+pub fn capture<R, F: FnOnce() -> R>(day: u8, level: Level, f: F) -> (R, Vec<Event>) {
+    ACTIVE.store(true, Ordering::Relaxed);
+    let previous = CAPTURE.with(|capture| {
+        capture.replace(Some(Capture {
+            day,
+            level,
+            events: Vec::new(),
+        }))
+    });
+    let result = f();
+    let captured = CAPTURE.with(|capture| capture.replace(previous));
+    (result, captured.map(|c| c.events).unwrap_or_default())
+}
+
+/// Emit a trace event for a day, with any number of `key = value` fields
+/// whose values implement `Display`. Nothing is evaluated or formatted
+/// unless the event is enabled, as in
+/// `trace_event!(17, Level::Debug, "shot missed", x = x, y = y)`.
+#[macro_export]
+macro_rules! trace_event {
+    ($day:expr, $level:expr, $name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled($day, $level) {
+            $crate::trace::emit(
+                $day,
+                $level,
+                $name,
+                &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*],
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Held by any test that depends on day 0's global level, which stays
+    /// off whenever it isn't held
+    static DAY_0: Mutex<()> = Mutex::new(());
+
+    /// Exclusive use of day 0's global level, which is turned back off when
+    /// the test is done with it, even if it fails
+    struct Day0 {
+        _guard: MutexGuard<'static, ()>,
+    }
+
+    impl Day0 {
+        fn lock() -> Self {
+            Day0 {
+                _guard: DAY_0
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            }
+        }
+    }
+
+    impl Drop for Day0 {
+        fn drop(&mut self) {
+            disable(0);
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        let _day_0 = Day0::lock();
+        assert_eq!("debug".parse::<Level>().unwrap(), Level::Debug);
+        assert!("loud".parse::<Level>().is_err());
+        assert!(Level::Info < Level::Trace);
+
+        // Day 0 isn't a puzzle, so no other test emits events for it
+        assert!(!enabled(0, Level::Info));
+        enable(0, Level::Debug);
+        assert!(enabled(0, Level::Info));
+        assert!(enabled(0, Level::Debug));
+        assert!(!enabled(0, Level::Trace));
+        disable(0);
+        assert!(!enabled(0, Level::Info));
+        enable(26, Level::Trace);
+        assert!(!enabled(26, Level::Info));
+    }
+
+    #[test]
+    fn test_capture() {
+        let _day_0 = Day0::lock();
+        let mut evaluated = 0;
+        let (answer, events) = capture(0, Level::Debug, || {
+            trace_event!(0, Level::Info, "started");
+            trace_event!(0, Level::Debug, "step", n = 1, label = "one");
+            trace_event!(
+                0,
+                Level::Trace,
+                "detail",
+                n = {
+                    evaluated += 1;
+                    evaluated
+                }
+            );
+            trace_event!(1, Level::Info, "another day");
+            42
+        });
+        assert_eq!(answer, 42);
+        assert_eq!(evaluated, 0);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].to_string(), "[Day 0 DEBUG] step n=1 label=one");
+        assert_eq!(events[1].field("label"), Some("one"));
+        assert_eq!(events[1].field("missing"), None);
+
+        // The capture only lasts for the closure
+        assert!(!enabled(0, Level::Info));
+    }
+}
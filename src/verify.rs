@@ -0,0 +1,151 @@
+//! Known-correct answers for the puzzle inputs, for catching regressions in
+//! the solvers outside of the sample-data unit tests.
+//!
+//! The answers file holds one `<day> <star> <answer>` line per star. Blank
+//! lines and lines starting with '#' are ignored.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::answer::Answer;
+use crate::parser::{LineParser, ParseError};
+
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+/// How an answer compares with the expected one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// There is no expected answer for this star
+    Missing {
+        actual: String,
+    },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Fail { expected, actual } => {
+                write!(f, "FAIL: expected {}, got {}", expected, actual)
+            }
+            Verdict::Missing { actual } => write!(f, "MISSING: got {}", actual),
+        }
+    }
+}
+
+fn parse_line(parser: &mut LineParser) -> Result<((u8, u8), String), ParseError> {
+    let day = parser.integer::<u8>()?;
+    parser.expect(" ")?;
+    let star = parser.char_matching("a star (1 or 2)", |c| c == '1' || c == '2')?;
+    parser.expect(" ")?;
+    if parser.is_empty() {
+        return Err(parser.error("an answer"));
+    }
+    let answer = parser.rest().to_string();
+    Ok(((day, star as u8 - b'0'), answer))
+}
+
+impl Expected {
+    /// Load the answers file from the puzzle input directory
+    pub fn data<P: AsRef<std::path::Path>>(data_dir: P) -> Result<Self> {
+        let answers_file = data_dir.as_ref().join(ANSWERS_FILE);
+        let file = std::fs::File::open(&answers_file)
+            .with_context(|| format!("Unable to open {}", answers_file.display()))?;
+        Self::parse_reader(std::io::BufReader::new(file))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(data: I) -> Result<Self, ParseError> {
+        let mut answers = BTreeMap::new();
+        for (i, line) in data.enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let (key, answer) = parse_line(&mut LineParser::new(i + 1, &line))?;
+            answers.insert(key, answer);
+        }
+        Ok(Expected { answers })
+    }
+
+    pub fn parse_reader<R: BufRead>(data: R) -> Result<Self> {
+        let lines = data.lines().collect::<std::io::Result<Vec<String>>>()?;
+        Ok(Self::parse(lines.into_iter())?)
+    }
+
+    pub fn parse_str(data: &str) -> Result<Self> {
+        Self::parse_reader(data.as_bytes())
+    }
+
+    pub fn get(&self, day: u8, star: u8) -> Option<&str> {
+        self.answers.get(&(day, star)).map(String::as_str)
+    }
+
+    /// Compare `answer` to the expected answer for the given star
+    pub fn check(&self, day: u8, star: u8, answer: &Answer) -> Verdict {
+        let actual = answer.to_string();
+        match self.get(day, star) {
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_string(),
+                actual,
+            },
+            None => Verdict::Missing { actual },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_DATA: &str = "\
+# Sample answers
+
+1 1 7
+1 2 5
+13 2 AHPRPAUZ
+";
+
+    #[test]
+    fn test_parse() {
+        let expected = Expected::parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(expected.get(1, 2), Some("5"));
+        assert_eq!(expected.get(13, 2), Some("AHPRPAUZ"));
+        assert_eq!(expected.get(13, 1), None);
+
+        let err = Expected::parse_str("1 1 7\n1 3 5\n").unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(Expected::parse_str("1 1\n").is_err());
+        assert!(Expected::parse_str("x 1 7\n").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = Expected::parse_str(SAMPLE_DATA).unwrap();
+        assert_eq!(expected.check(1, 1, &Answer::Integer(7)), Verdict::Pass);
+        assert_eq!(
+            expected.check(1, 2, &Answer::Integer(6)).to_string(),
+            "FAIL: expected 5, got 6"
+        );
+        assert_eq!(expected.check(13, 2, &"AHPRPAUZ".into()), Verdict::Pass);
+        assert_eq!(
+            expected.check(2, 1, &Answer::Integer(1)),
+            Verdict::Missing {
+                actual: "1".to_string()
+            }
+        );
+    }
+}